@@ -0,0 +1,119 @@
+use std::hint::black_box;
+
+use bitvec::{field::BitField, order::Msb0, view::AsMutBits};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use dectdump::{
+    crc::{crc_bits, Rcrc},
+    decoder::{BitIterator, Decoder, FP_SYNC, PP_SYNC},
+};
+
+/// Preamble and FP sync word.
+const S_FIELD: u32 = 0xAAAA_E98A;
+/// A CRC-valid A-field taken from a real capture.
+const A_FIELD: u64 = 0x6110_2af1_2c0d_3cd5;
+/// Bits per DECT slot, including guard space.
+const SLOT_BITS: usize = 480;
+
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+/// Noise with a valid FP burst starting every slot, at an offset that is not byte aligned.
+fn capture(len: usize) -> Vec<u8> {
+    let mut data = noise(len);
+    let bits = data.as_mut_bits::<Msb0>();
+    let mut pos = 3;
+    while pos + 96 <= bits.len() {
+        bits[pos..pos + 32].store_be(S_FIELD);
+        bits[pos + 32..pos + 96].store_be(A_FIELD);
+        pos += SLOT_BITS;
+    }
+    data
+}
+
+fn bit_iterator(c: &mut Criterion) {
+    let data = noise(64 * 1024);
+    let mut group = c.benchmark_group("bit_iterator");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("next", |b| {
+        b.iter(|| BitIterator::new(&data).fold(0u64, |acc, n| acc ^ n))
+    });
+    group.bench_function("nth_64", |b| {
+        b.iter(|| {
+            let mut iter = BitIterator::new(&data);
+            let mut acc = 0u64;
+            while let Some(n) = iter.nth(63) {
+                acc ^= n;
+            }
+            acc
+        })
+    });
+    group.finish();
+}
+
+fn sync(c: &mut Criterion) {
+    let data = noise(64 * 1024);
+    let mut group = c.benchmark_group("sync");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("find_sync", |b| {
+        b.iter_batched_ref(
+            || BitIterator::new(&data),
+            |iter| while iter.find_sync(&[FP_SYNC, PP_SYNC], 0).is_some() {},
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("iterator_find", |b| {
+        b.iter_batched_ref(
+            || BitIterator::new(&data),
+            |iter| {
+                let is_sync = |n: &u64| {
+                    let n = (*n & 0xffffff) as u32;
+                    n == FP_SYNC || n == PP_SYNC
+                };
+                while iter.find(is_sync).is_some() {}
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn crc(c: &mut Criterion) {
+    let a_field = A_FIELD.to_be_bytes();
+    let b_field = noise(40);
+
+    let mut group = c.benchmark_group("crc");
+    group.bench_function("rcrc_a_field", |b| b.iter(|| black_box(&a_field).crc()));
+    group.bench_function("crc_bits_40_bytes", |b| {
+        b.iter(|| crc_bits(black_box(&b_field)))
+    });
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let data = capture(1024 * 1024);
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(20);
+    group.bench_function("file", |b| {
+        b.iter(|| {
+            let mut decoder = Decoder::new(BitIterator::new([]));
+            decoder.push(black_box(&data)).unwrap().len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bit_iterator, sync, crc, decode);
+criterion_main!(benches);
@@ -0,0 +1,4 @@
+//! Builds libdectdump, the C API in `dectdump::ffi` as a shared library; see
+//! `include/dectdump.h`.
+
+pub use dectdump_core::ffi::*;
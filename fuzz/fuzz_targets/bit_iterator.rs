@@ -0,0 +1,25 @@
+//! Sync search and cursor movement over arbitrary input, fed in arbitrary pieces.
+
+#![no_main]
+
+use dectdump::decoder::{BitIterator, FP_SYNC, PP_SYNC};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&control, data)) = data.split_first() else {
+        return;
+    };
+    let max_errors = u32::from(control & 0x7);
+    let chunk = usize::from(control >> 3) + 1;
+
+    let mut bits = BitIterator::new([]).with_limit(256);
+    for piece in data.chunks(chunk) {
+        bits.extend(piece.iter().copied());
+        while bits.find_sync(&[FP_SYNC, PP_SYNC], max_errors).is_some() {
+            let before = bits.position();
+            let _ = bits.peek_bits(usize::from(piece[0]));
+            let _ = bits.nth(usize::from(piece[piece.len() - 1]));
+            assert!(bits.position() >= before);
+        }
+    }
+});
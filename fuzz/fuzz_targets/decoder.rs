@@ -0,0 +1,24 @@
+//! The full MAC state machine over arbitrary input, with every decoder option.
+
+#![no_main]
+
+use dectdump::decoder::{BitIterator, Decoder};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&control, data)) = data.split_first() else {
+        return;
+    };
+
+    let bits = BitIterator::new([]).with_limit(1024);
+    let mut decoder = Decoder::new(bits)
+        .with_sync_errors(u32::from(control & 0x3))
+        .with_error_correction(control & 0x4 != 0)
+        .with_frame_timing(control & 0x8 != 0)
+        .with_lock_thresholds(u32::from(control >> 4), 1);
+
+    let chunk = usize::from(control >> 4) * 16 + 1;
+    for piece in data.chunks(chunk) {
+        decoder.push(piece).unwrap();
+    }
+});
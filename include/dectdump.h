@@ -0,0 +1,73 @@
+/*
+ * C API of the dectdump decoder. Build the shared library with
+ * `cargo build --release -p dectdump-ffi` and link against libdectdump.
+ *
+ *     DectDecoder *decoder = dect_decoder_new(false);
+ *     dect_decoder_push(decoder, buf, len);
+ *     DectPacket packet;
+ *     while (dect_decoder_pull(decoder, &packet)) { ... }
+ *     dect_decoder_free(decoder);
+ */
+
+#ifndef DECTDUMP_H
+#define DECTDUMP_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <sys/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define DECT_PACKET_HEADER 0
+#define DECT_PACKET_A 1
+#define DECT_PACKET_GAP 2
+#define DECT_B_FIELD_MAX 64
+
+typedef struct DectDecoder DectDecoder;
+
+typedef struct DectPacket {
+    /* DECT_PACKET_HEADER, DECT_PACKET_A or DECT_PACKET_GAP. */
+    uint8_t kind;
+    /* Low 16 bits of the sync word, e.g. 0xe98a from a fixed part (headers only). */
+    uint16_t sync;
+    /* A-field header octet. */
+    uint8_t header;
+    /* The 40-bit A-field tail. */
+    uint8_t tail[5];
+    /* The received R-CRC. */
+    uint16_t crc;
+    /* Whether a bit error in the A-field was corrected. */
+    bool corrected;
+    /* Number of valid bits in b; zero if there is no B-field. */
+    size_t b_bits;
+    /* The B-field, MSB first. */
+    uint8_t b[DECT_B_FIELD_MAX];
+    /* Length of a gap in bits (gaps only). */
+    uint64_t gap_bits;
+    /* Whether the gap's input was discarded unread (gaps only). */
+    bool gap_dropped;
+} DectPacket;
+
+/* Creates a decoder, optionally repairing single bit errors in the A-field. */
+DectDecoder *dect_decoder_new(bool correct_errors);
+
+/* Frees a decoder. NULL is ignored. */
+void dect_decoder_free(DectDecoder *decoder);
+
+/* Feeds len bytes of the bit stream (MSB first). Returns the number of packets waiting to be
+ * pulled, or -1 on error. Any length is accepted; input larger than the decoder's buffer is
+ * decoded a piece at a time. Waiting packets are kept until pulled, so pull them all after
+ * every push. */
+ssize_t dect_decoder_push(DectDecoder *decoder, const uint8_t *data, size_t len);
+
+/* Moves the oldest waiting packet into *out. Returns false if there is none. */
+bool dect_decoder_pull(DectDecoder *decoder, DectPacket *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,202 @@
+use std::{
+    io::{self, ErrorKind},
+    sync::{atomic::AtomicU64, Arc},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Error, Result};
+use dectdump::{
+    clock::Clock,
+    decoder::{DecodedPacket, Decoder, ErrorStats, Lock},
+    error::DecodeError,
+    stream::{DataSource, DecoderStream},
+};
+use tokio_stream::StreamExt;
+
+use crate::queue::Sender;
+
+/// Any input a channel can read from.
+pub type Source = Box<dyn DataSource + Send>;
+
+/// One bit stream with its own decoder.
+pub struct Channel {
+    stream: DecoderStream<Source>,
+    index: usize,
+    /// Where the input comes from, for messages.
+    name: String,
+
+    /// Lock state last reported to the user.
+    lock: Lock,
+
+    /// How often to report error rates, if at all.
+    error_report: Option<Duration>,
+    /// Error counts and time of the last error rate report.
+    last_errors: (ErrorStats, Instant),
+}
+
+impl Channel {
+    pub fn new(
+        index: usize,
+        source: Source,
+        name: String,
+        carrier: Option<u8>,
+        decoder: Decoder,
+    ) -> Self {
+        let mut stream = DecoderStream::new(source, decoder).with_channel(index);
+        if let Some(carrier) = carrier {
+            stream = stream.with_carrier(carrier);
+        }
+
+        Self {
+            index,
+            name,
+            lock: stream.decoder().lock(),
+            error_report: None,
+            last_errors: (ErrorStats::default(), Instant::now()),
+            stream,
+        }
+    }
+
+    /// Sets where packet receive times come from.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.stream = self.stream.with_clock(clock);
+        self
+    }
+
+    /// Reports the bits counted in `dropped` as dropped gaps; see
+    /// [`DecoderStream::with_dropped`].
+    pub fn with_dropped(mut self, dropped: Arc<AtomicU64>) -> Self {
+        self.stream = self.stream.with_dropped(dropped);
+        self
+    }
+
+    /// Prints the estimated bit error rate and CRC failure rate every `interval`, checked as
+    /// packets arrive.
+    pub fn with_error_report(mut self, interval: Duration) -> Self {
+        self.error_report = Some(interval);
+        self
+    }
+
+    /// Decodes until the input ends or fails, or the receiving end is dropped.
+    ///
+    /// Packets are tagged with the channel index. Errors a socket recovers from, such as a
+    /// refused UDP receive after an ICMP message, are printed and decoding goes on. Any other
+    /// input error ends only this channel; it is forwarded as the last item, for the receiver
+    /// to report.
+    pub async fn run(mut self, tx: Sender<Result<DecodedPacket>>) {
+        while let Some(packet) = self.stream.next().await {
+            self.report_lock();
+            self.report_errors();
+
+            if let Err(DecodeError::Source(e)) = &packet {
+                if is_transient(e) {
+                    eprintln!("channel {} ({}): {e}", self.index, self.name);
+                    continue;
+                }
+            }
+            let packet = packet.map_err(|e| {
+                Error::from(e).context(format!("channel {} ({})", self.index, self.name))
+            });
+            let failed = packet.is_err();
+            if tx.send(packet).await.is_err() || failed {
+                return;
+            }
+        }
+    }
+
+    /// Prints the error rates over the last interval, once it has passed.
+    fn report_errors(&mut self) {
+        let Some(interval) = self.error_report else {
+            return;
+        };
+        let (last, since) = self.last_errors;
+        if since.elapsed() < interval {
+            return;
+        }
+
+        let stats = self.stream.decoder().error_stats();
+        self.last_errors = (stats, Instant::now());
+        let errors = stats - last;
+
+        let ber = match errors.bit_error_rate() {
+            Some(ber) => format!("{ber:.1e}"),
+            None => "-".to_string(),
+        };
+        let failures = errors.crc_failure_rate().unwrap_or(0.0) * 100.0;
+        eprintln!(
+            "channel {} ({}): BER {ber}, {failures:.1}% CRC failures, {} good bursts",
+            self.index, self.name, errors.good
+        );
+    }
+
+    /// Prints a status line when the decoder gains or loses lock.
+    fn report_lock(&mut self) {
+        let lock = self.stream.decoder().lock();
+        if lock == self.lock {
+            return;
+        }
+        self.lock = lock;
+
+        match lock {
+            Lock::Locked => eprintln!("channel {} ({}): locked", self.index, self.name),
+            Lock::Searching => eprintln!("channel {} ({}): lost lock", self.index, self.name),
+        }
+    }
+}
+
+/// Whether reading from a socket can go on after `e`.
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ConnectionRefused
+            | ErrorKind::Interrupted
+            | ErrorKind::TimedOut
+            | ErrorKind::WouldBlock
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, ErrorKind};
+
+    use dectdump::{
+        decoder::{BitIterator, Decoder, Packet},
+        encode::{slots, AField, Burst},
+    };
+
+    use super::Channel;
+    use crate::queue::{self, Overflow};
+
+    #[tokio::test]
+    async fn test_input_errors() {
+        // Padded so that every chunk decodes on its own.
+        let mut burst = vec![0; 8];
+        burst.extend(
+            slots([Burst::fp(AField::nt([1; 5])), Burst::pp(AField::nt([2; 5]))]).into_vec(),
+        );
+        burst.extend([0; 8]);
+        let chunks = [
+            Ok(burst.clone()),
+            Err(io::Error::from(ErrorKind::ConnectionRefused)),
+            Ok(burst.clone()),
+            Err(io::Error::other("gone")),
+            Ok(burst),
+        ];
+        let source = Box::new(tokio_stream::iter(chunks));
+        let decoder = Decoder::new(BitIterator::new([]));
+        let channel = Channel::new(0, source, "test".into(), None, decoder);
+
+        let (tx, mut rx) = queue::bounded(64, Overflow::Block);
+        channel.run(tx).await;
+        let mut a_fields = 0;
+        let mut errors = 0;
+        while let Some(packet) = rx.recv().await {
+            match packet {
+                Ok(packet) => a_fields += usize::from(matches!(packet.packet, Packet::A { .. })),
+                Err(_) => errors += 1,
+            }
+        }
+        // Past the refused receive, up to the other error, which ends the channel.
+        assert_eq!((a_fields, errors), (4, 1));
+    }
+}
@@ -0,0 +1,90 @@
+//! Where the receive times of packets come from.
+//!
+//! Live input is stamped with the wall clock as it arrives. A recorded capture has no timestamps
+//! of its own, but the bit stream runs at a fixed rate, so a [`CaptureClock`] works out when each
+//! burst was on the air from its position and the time the recording started. Decoding the file
+//! then gives the same times however fast it is read.
+
+use core::fmt::Debug;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::modulate::BIT_RATE;
+
+/// Assigns receive times to packets, as time since the Unix epoch.
+pub trait Clock: Debug + Send {
+    /// Called whenever a chunk of input arrives.
+    fn chunk_received(&mut self) {}
+
+    /// The receive time of a packet whose burst starts at bit `position` of the input.
+    fn time(&self, position: u64) -> Option<Duration>;
+}
+
+/// The system clock when the chunk holding a burst arrived.
+#[derive(Debug, Clone, Default)]
+pub struct WallClock {
+    last_chunk: Option<Duration>,
+}
+
+impl Clock for WallClock {
+    fn chunk_received(&mut self) {
+        self.last_chunk = SystemTime::now().duration_since(UNIX_EPOCH).ok();
+    }
+
+    fn time(&self, _position: u64) -> Option<Duration> {
+        self.last_chunk
+    }
+}
+
+/// A recording's start time plus the bit position at the nominal bit rate.
+#[derive(Debug, Clone)]
+pub struct CaptureClock {
+    start: Duration,
+}
+
+impl CaptureClock {
+    /// A clock for a recording whose first bit was received at `start`.
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            start: start.duration_since(UNIX_EPOCH).unwrap_or_default(),
+        }
+    }
+
+    /// How long `bits` take on the air.
+    pub fn duration(bits: u64) -> Duration {
+        Duration::from_secs_f64(bits as f64 / BIT_RATE)
+    }
+}
+
+impl Clock for CaptureClock {
+    fn time(&self, position: u64) -> Option<Duration> {
+        Some(self.start + Self::duration(position))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{CaptureClock, Clock, WallClock};
+
+    #[test]
+    fn test_wall_clock() {
+        let mut clock = WallClock::default();
+        assert_eq!(clock.time(0), None);
+        clock.chunk_received();
+        let time = clock.time(0).unwrap();
+        assert_eq!(clock.time(1_000_000), Some(time));
+    }
+
+    #[test]
+    fn test_capture_clock() {
+        let clock = CaptureClock::new(UNIX_EPOCH + Duration::from_secs(100));
+        assert_eq!(clock.time(0), Some(Duration::from_secs(100)));
+        // One frame later.
+        assert_eq!(clock.time(11_520), Some(Duration::from_millis(100_010)));
+        assert_eq!(
+            CaptureClock::duration(1_152_000 * 60),
+            Duration::from_secs(60)
+        );
+    }
+}
@@ -0,0 +1,1818 @@
+//! Sync detection and MAC layer framing of a raw DECT bit stream.
+
+use alloc::vec::Vec;
+use core::{fmt, time::Duration};
+
+use bitvec::{field::BitField, order::Msb0, slice::BitSlice, vec::BitVec, view::AsBits};
+
+use crate::{
+    crc::{self, Rcrc},
+    error::{DecodeError, Result},
+    scramble::scramble,
+};
+
+/// Last 24 bits of the S-field sent by a fixed part (base station).
+pub const FP_SYNC: u32 = 0xAAE98A;
+/// Last 24 bits of the S-field sent by a portable part (handset).
+pub const PP_SYNC: u32 = 0x551675;
+
+/// The S-field patterns a [`Decoder`] hunts for: the last 24 bits of the S-field, i.e. the end of
+/// the preamble and the 16-bit sync word, of each direction.
+///
+/// A prolonged preamble only adds bits in front of these, so the standard patterns find such
+/// bursts too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncWords {
+    /// Sent by fixed parts.
+    pub fp: u32,
+    /// Sent by portable parts.
+    pub pp: u32,
+}
+
+impl SyncWords {
+    /// [`FP_SYNC`] and [`PP_SYNC`].
+    pub const DECT: Self = Self {
+        fp: FP_SYNC,
+        pp: PP_SYNC,
+    };
+
+    /// Who sent a burst, going by the low 16 bits of its sync word (as in [`Packet::Header`], but
+    /// in host order). `None` if it matches neither pattern exactly.
+    pub fn classify(&self, sync: u16) -> Option<Sender> {
+        if u32::from(sync) == self.fp & 0xffff {
+            Some(Sender::FixedPart)
+        } else if u32::from(sync) == self.pp & 0xffff {
+            Some(Sender::PortablePart)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for SyncWords {
+    fn default() -> Self {
+        Self::DECT
+    }
+}
+
+/// The side of the link a burst came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sender {
+    /// A base station.
+    FixedPart,
+    /// A handset.
+    PortablePart,
+}
+
+/// Bits per slot at 1.152 Mbit/s, including the guard space.
+pub const SLOT_BITS: u64 = 480;
+/// Bits per 10 ms TDMA frame of 24 slots.
+pub const FRAME_BITS: u64 = 24 * SLOT_BITS;
+
+/// BA code of an A-field header that is not followed by a B-field.
+pub const BA_NO_B_FIELD: u8 = 7;
+/// Bits in the B-field of a full slot. The 4-bit X-field after it is not read.
+pub const B_FIELD_BITS: usize = 320;
+
+/// How far (in bits) a sync may sit from the slot grid and still count as on time.
+const SLOT_TOLERANCE: u64 = 8;
+/// How long the slot grid of the last good frame is trusted, in bits (one multiframe).
+const GRID_LIFETIME: u64 = 16 * FRAME_BITS;
+
+/// A decoded piece of a burst.
+#[derive(Debug)]
+pub enum Packet {
+    /// A sync word was found; the start of a burst.
+    ///
+    /// The receiver metadata (everything up to `rssi`) is zero, as a bare bit stream carries none.
+    Header {
+        /// Receiver mode.
+        rxmode: u8,
+        /// RF carrier number.
+        channel: u8,
+        /// Slot within the frame.
+        slot: u16,
+        /// Frame number within the multiframe.
+        frameno: u8,
+        /// Received signal strength.
+        rssi: u8,
+        /// The 24 bits before the sync word.
+        preamble: [u8; 3],
+        /// The low 16 bits of the sync word, in network byte order: read it with
+        /// `u16::from_be`.
+        sync: u16,
+    },
+    /// An A-field that passed its R-CRC, with the B-field after it.
+    A {
+        /// A-field header octet.
+        header: u8,
+        /// The 40-bit A-field tail.
+        tail: [u8; 5],
+        /// The received R-CRC.
+        crc: u16,
+        /// The B-field, if the header announces one, still scrambled (see [`Packet::descramble`]).
+        b: Option<BitVec<u8, Msb0>>,
+        /// A single bit error in the A-field was corrected using the R-CRC.
+        corrected: bool,
+    },
+    /// A stretch of input without decoded bursts: input was discarded because the decoder fell
+    /// behind, or no burst passed its CRC for more than a multiframe. In the latter case it comes
+    /// right before the A-field that ended the gap.
+    Gap {
+        /// Length of the gap in bits. At 1.152 Mbit/s, [`FRAME_BITS`] are one 10 ms frame.
+        bits: u64,
+        /// The input was discarded unread, as opposed to not decoding.
+        dropped: bool,
+    },
+}
+
+impl Packet {
+    /// Descrambles the B-field of an A packet received in frame `frame`. The decoder hands out
+    /// B-fields as received, since it does not know the frame number.
+    pub fn descramble(&mut self, frame: u8) {
+        if let Packet::A { b: Some(b), .. } = self {
+            scramble(frame, b);
+        }
+    }
+}
+
+/// `bits` packed into bytes MSB first, the last byte padded with zeros.
+///
+/// A B-field's backing storage starts wherever its burst happened to start within an input byte,
+/// so use this rather than its raw bytes.
+pub fn pack_bits(bits: &BitSlice<u8, Msb0>) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|byte| byte.load_be::<u8>() << (8 - byte.len()))
+        .collect()
+}
+
+impl Packet {
+    /// Displays the packet telling senders apart by `syncs` rather than the standard patterns.
+    pub fn display_with(&self, syncs: SyncWords) -> PacketDisplay<'_> {
+        PacketDisplay {
+            packet: self,
+            syncs,
+        }
+    }
+}
+
+/// One line such as `A Nt tail=0123456789 crc=beef`, or with `{:#}` one field per line.
+///
+/// Headers are marked `FP` or `PP` by the standard sync words; see [`Packet::display_with`].
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display_with(SyncWords::DECT).fmt(f)
+    }
+}
+
+/// A [`Packet`] displayed with the sync words its channel uses.
+#[derive(Debug)]
+pub struct PacketDisplay<'a> {
+    packet: &'a Packet,
+    syncs: SyncWords,
+}
+
+impl fmt::Display for PacketDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expanded = f.alternate();
+        match self.packet {
+            Packet::Header { preamble, sync, .. } => {
+                let sync = u16::from_be(*sync);
+                let sender = match self.syncs.classify(sync) {
+                    Some(Sender::FixedPart) => "FP",
+                    Some(Sender::PortablePart) => "PP",
+                    None => "inexact",
+                };
+                let [p0, p1, p2] = preamble;
+                if expanded {
+                    writeln!(f, "S-field")?;
+                    writeln!(f, "  preamble  {p0:02x}{p1:02x}{p2:02x}")?;
+                    write!(f, "  sync      {sync:04x} ({sender})")
+                } else {
+                    write!(
+                        f,
+                        "S preamble={p0:02x}{p1:02x}{p2:02x} sync={sync:04x} {sender}"
+                    )
+                }
+            }
+            Packet::A {
+                header,
+                tail,
+                crc,
+                b,
+                corrected,
+            } => {
+                let ta = TailType::from_header(*header);
+                let [t0, t1, t2, t3, t4] = tail;
+                if expanded {
+                    writeln!(f, "A-field")?;
+                    writeln!(f, "  tail type {ta} ({ta:#})")?;
+                    writeln!(
+                        f,
+                        "  header    {header:02x}: Q1 {} BA {} Q2 {}",
+                        header >> 4 & 1,
+                        header >> 1 & 7,
+                        header & 1
+                    )?;
+                    writeln!(f, "  tail      {t0:02x}{t1:02x}{t2:02x}{t3:02x}{t4:02x}")?;
+                    write!(f, "  R-CRC     {crc:04x}")?;
+                    if *corrected {
+                        write!(f, " (one bit corrected)")?;
+                    }
+                    match b {
+                        Some(b) => write!(f, "\n  B-field   {} bits", b.len()),
+                        None => write!(f, "\n  B-field   none"),
+                    }
+                } else {
+                    write!(
+                        f,
+                        "A {ta} tail={t0:02x}{t1:02x}{t2:02x}{t3:02x}{t4:02x} crc={crc:04x}"
+                    )?;
+                    if let Some(b) = b {
+                        write!(f, " b={}", b.len())?;
+                    }
+                    if *corrected {
+                        write!(f, " corrected")?;
+                    }
+                    Ok(())
+                }
+            }
+            Packet::Gap { bits, dropped } => {
+                let kind = if *dropped { "dropped" } else { "not decoded" };
+                let frames = bits / FRAME_BITS;
+                if expanded {
+                    writeln!(f, "Gap")?;
+                    write!(f, "  {bits} bits ({frames} frames) {kind}")
+                } else {
+                    write!(f, "Gap bits={bits} frames={frames} {kind}")
+                }
+            }
+        }
+    }
+}
+
+/// A packet tagged with where and when it was received.
+#[derive(Debug)]
+pub struct DecodedPacket {
+    /// Index of the input channel.
+    pub channel: usize,
+    /// RF carrier the channel is tuned to, if known.
+    pub carrier: Option<u8>,
+    /// Bit offset of the burst's S-field in the channel's input (see
+    /// [`Decoder::burst_position`]).
+    pub position: u64,
+    /// The frame the burst was sent in, if known (see [`Decoder::burst_frame`]).
+    pub frame: Option<Frame>,
+    /// The slot the burst was sent in, if known (see [`Decoder::burst_slot`]).
+    pub slot: Option<Slot>,
+    /// When the input completing the packet arrived, since the Unix epoch.
+    pub received: Option<Duration>,
+    /// The sync words the channel hunts for, to tell senders apart by.
+    pub syncs: SyncWords,
+    /// The packet itself.
+    pub packet: Packet,
+}
+
+/// The slot a burst was sent in, inferred from its position in the bit stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    /// Slot number within the frame, 0 to 23. Fixed parts send in slots 0 to 11, portable parts
+    /// in 12 to 23.
+    pub number: u8,
+    /// The numbering comes from a slot number a fixed part broadcast in its Qt static system
+    /// information. Otherwise it was guessed from the first burst seen, which is assumed to be in
+    /// slot 0 (or 12 from a portable part), and is only consistent relative to other bursts.
+    pub confirmed: bool,
+}
+
+/// The frame a burst was sent in, inferred from its position in the bit stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Number of the multiframe (16 frames), once a fixed part has broadcast it.
+    pub multiframe: Option<u32>,
+    /// Frame number within the multiframe, 0 to 15.
+    pub number: u8,
+    /// The numbering is anchored to a fixed part's Qt broadcast, which is only sent in frame 8.
+    /// Otherwise the first burst seen is assumed to be in frame 0.
+    pub confirmed: bool,
+}
+
+/// Frame and slot timing carried from burst to burst, counted from the last burst that passed its
+/// CRC.
+///
+/// A [`Decoder`] keeps one to time its packets. [`track`](Self::track) replays it over packets
+/// decoded elsewhere, e.g. by several decoders each given a piece of a capture, to number them
+/// as a single decoder reading the whole capture would have.
+#[derive(Debug, Clone, Default)]
+pub struct Timing {
+    /// Start of the sync window of a recent good burst and the frame and slot it was in.
+    anchor: Option<(u64, Frame, Slot)>,
+    /// Whether the last sync word was closer to the fixed part's.
+    fp: bool,
+    /// Frame and slot of the burst the last packet belongs to.
+    last: Option<(Frame, Slot)>,
+}
+
+impl Timing {
+    /// The frame and slot of `packet`, going by the packets tracked before it.
+    ///
+    /// A gap that is not [`dropped`](Packet::Gap::dropped) gets the time of the A-field after
+    /// it, so its time is known only once that has been tracked; see [`gap`](Self::gap).
+    pub fn track(&mut self, packet: &DecodedPacket) -> Option<(Frame, Slot)> {
+        // The sync window starts 32 bits before the S-field.
+        let start = packet.position.saturating_sub(32);
+        match &packet.packet {
+            Packet::Header { preamble, sync, .. } => {
+                let candidate = u32::from(preamble[2]) << 16 | u32::from(u16::from_be(*sync));
+                let SyncWords { fp, pp } = packet.syncs;
+                self.fp = (candidate ^ fp).count_ones() <= (candidate ^ pp).count_ones();
+                self.last = self.infer(start);
+            }
+            Packet::A {
+                header, tail, crc, ..
+            } => {
+                let [crc0, crc1] = crc.to_be_bytes();
+                let [t0, t1, t2, t3, t4] = *tail;
+                let a_field = [*header, t0, t1, t2, t3, t4, crc0, crc1];
+                self.last = Some(self.anchor(start, self.fp, &a_field));
+            }
+            Packet::Gap { .. } => {}
+        }
+        self.last
+    }
+
+    /// The length of the gap a decoder reports before an A-field whose burst's S-field is at
+    /// `position`: the bits since the last burst that passed its CRC, if more than a multiframe.
+    ///
+    /// Call it before tracking the A-field.
+    pub fn gap(&self, position: u64) -> Option<u64> {
+        let (anchor, ..) = self.anchor?;
+        let bits = position.saturating_sub(32).checked_sub(anchor)?;
+        (bits > GRID_LIFETIME).then_some(bits)
+    }
+
+    /// The frame and slot of a burst whose sync window starts at `start`, counted from the
+    /// anchor.
+    fn infer(&self, start: u64) -> Option<(Frame, Slot)> {
+        let (anchor, frame, slot) = self.anchor?;
+        let distance = start.checked_sub(anchor)?;
+        if distance > GRID_LIFETIME {
+            return None;
+        }
+        let slots = u64::from(slot.number) + (distance + SLOT_BITS / 2) / SLOT_BITS;
+        let frames = u64::from(frame.number) + slots / 24;
+        Some((
+            Frame {
+                multiframe: frame
+                    .multiframe
+                    .map(|multiframe| ((u64::from(multiframe) + frames / 16) & 0xff_ffff) as u32),
+                number: (frames % 16) as u8,
+                confirmed: frame.confirmed,
+            },
+            Slot {
+                number: (slots % 24) as u8,
+                confirmed: slot.confirmed,
+            },
+        ))
+    }
+
+    /// Moves the anchor to a burst that just passed its CRC, taking whatever a fixed part's Qt
+    /// broadcast tells about the frame structure, and returns the burst's frame and slot.
+    fn anchor(&mut self, start: u64, fp: bool, a_field: &[u8; 8]) -> (Frame, Slot) {
+        let (mut frame, mut slot) = self.infer(start).unwrap_or((
+            Frame {
+                multiframe: None,
+                number: 0,
+                confirmed: false,
+            },
+            Slot {
+                number: if fp { 0 } else { 12 },
+                confirmed: false,
+            },
+        ));
+
+        if let Some(tail) = qt_tail(a_field).filter(|_| fp) {
+            // Fixed parts send Qt in frame 8 only.
+            frame.number = 8;
+            frame.confirmed = true;
+            if let Some(number) = qt_slot_number(tail) {
+                slot = Slot {
+                    number,
+                    confirmed: true,
+                };
+            }
+            if let Some(multiframe) = qt_multiframe_number(tail) {
+                frame.multiframe = Some(multiframe);
+            }
+        }
+
+        self.anchor = Some((start, frame, slot));
+        (frame, slot)
+    }
+}
+
+/// Running error counts of a decoder, from which the channel's bit error rate is estimated.
+///
+/// Subtract an earlier snapshot to get the counts over an interval.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorStats {
+    /// Bursts whose A-field passed its R-CRC, possibly after correction.
+    pub good: u64,
+    /// Sync matches whose A-field failed its R-CRC. These include false syncs in noise, more so
+    /// with tolerant sync matching.
+    pub crc_failures: u64,
+    /// Bits checked in good bursts: their sync words and A-fields.
+    pub bits: u64,
+    /// Errors found among those bits: sync word mismatches and corrected A-field bits.
+    pub bit_errors: u64,
+}
+
+impl ErrorStats {
+    /// Estimated bit error rate, if any good burst was seen.
+    ///
+    /// Only errors the decoder can see are counted, and bursts with more errors than it can
+    /// tolerate are missing entirely, so this underestimates high error rates.
+    pub fn bit_error_rate(&self) -> Option<f64> {
+        (self.bits > 0).then(|| self.bit_errors as f64 / self.bits as f64)
+    }
+
+    /// Fraction of sync matches whose A-field failed its R-CRC, if there were any.
+    pub fn crc_failure_rate(&self) -> Option<f64> {
+        let total = self.good + self.crc_failures;
+        (total > 0).then(|| self.crc_failures as f64 / total as f64)
+    }
+}
+
+impl core::ops::Sub for ErrorStats {
+    type Output = ErrorStats;
+
+    fn sub(self, earlier: ErrorStats) -> ErrorStats {
+        ErrorStats {
+            good: self.good.saturating_sub(earlier.good),
+            crc_failures: self.crc_failures.saturating_sub(earlier.crc_failures),
+            bits: self.bits.saturating_sub(earlier.bits),
+            bit_errors: self.bit_errors.saturating_sub(earlier.bit_errors),
+        }
+    }
+}
+
+/// Default cap on buffered, not yet consumed input (1 MiB).
+pub const DEFAULT_BUFFER_LIMIT: usize = 1 << 20;
+
+/// Rolling bit iterator that yields the next 64 bits at every bit offset.
+///
+/// Each call to `next` returns the 64 bits starting at the cursor and advances it by one bit.
+/// Bytes the cursor has fully moved past are dropped when new data is appended, and at most
+/// `limit` bytes are buffered: if a consumer falls that far behind, the oldest input is discarded.
+#[derive(Debug, Clone)]
+pub struct BitIterator {
+    inner: Vec<u8>,
+    /// Bit offset of the cursor into `inner`.
+    pos: usize,
+    /// Number of bits dropped from the front of `inner` so far.
+    offset: u64,
+    /// Number of those bits dropped unread because of `limit`.
+    overflowed: u64,
+    limit: usize,
+}
+
+impl BitIterator {
+    /// Starts at the beginning of `inner`, with the default buffer limit.
+    pub fn new(inner: impl AsRef<[u8]>) -> Self {
+        Self {
+            inner: inner.as_ref().to_vec(),
+            pos: 0,
+            offset: 0,
+            overflowed: 0,
+            limit: DEFAULT_BUFFER_LIMIT,
+        }
+    }
+
+    /// Sets the maximum number of bytes kept buffered.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self.compact();
+        self
+    }
+
+    /// Drops the bytes the cursor has moved past, then enforces `limit`.
+    fn compact(&mut self) {
+        let consumed = self.pos / 8;
+        if consumed > 0 {
+            self.inner.drain(..consumed);
+            self.pos -= consumed * 8;
+            self.offset += consumed as u64 * 8;
+        }
+
+        if self.inner.len() > self.limit {
+            let excess = self.inner.len() - self.limit;
+            self.inner.drain(..excess);
+            self.overflowed += (excess * 8).saturating_sub(self.pos) as u64;
+            self.pos = 0;
+            self.offset += excess as u64 * 8;
+        }
+    }
+
+    /// Number of bits discarded unread so far because the buffer limit was hit.
+    pub fn overflowed(&self) -> u64 {
+        self.overflowed
+    }
+
+    /// Drops everything buffered and records `bits` more as lost, for input that went missing
+    /// before it could be appended. Both count towards [`overflowed`](Self::overflowed).
+    pub fn discard(&mut self, bits: u64) {
+        let len = self.inner.len() * 8;
+        self.overflowed += (len - self.pos) as u64 + bits;
+        self.offset += len as u64 + bits;
+        self.inner.clear();
+        self.pos = 0;
+    }
+
+    /// Number of bytes that can be appended before the buffer limit discards unread input.
+    fn room(&self) -> usize {
+        self.limit.saturating_sub(self.inner.len() - self.pos / 8)
+    }
+
+    /// Absolute bit offset of the cursor since the first byte of the stream.
+    ///
+    /// Unlike the position within the buffer this is unaffected by compaction, so at a constant
+    /// bit rate it doubles as a clock.
+    pub fn position(&self) -> u64 {
+        self.offset + self.pos as u64
+    }
+}
+
+impl Extend<u8> for BitIterator {
+    fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
+        self.inner.extend(iter);
+        self.compact();
+    }
+}
+
+impl BitIterator {
+    /// Returns the 64 bits starting at bit offset `pos`, read straight from the byte buffer.
+    fn window(&self, pos: usize) -> Option<u64> {
+        if pos + 64 > self.inner.len() * 8 {
+            return None;
+        }
+
+        let byte = pos / 8;
+        let len = (self.inner.len() - byte).min(9);
+        let mut buf = [0u8; 16];
+        buf[..len].copy_from_slice(&self.inner[byte..byte + len]);
+
+        Some(((u128::from_be_bytes(buf) << (pos % 8)) >> 64) as u64)
+    }
+
+    /// Advances past the first window whose low 24 bits match one of `syncs` and returns it.
+    ///
+    /// A window matches if it differs from a sync word in at most `max_errors` bits.
+    ///
+    /// Equivalent to `find` with a sync predicate, but instead of re-reading 64 bits at every
+    /// offset it keeps a shift register and feeds it one bit per step. If no sync is found the
+    /// cursor is left where `next` would start returning `None`, so the search resumes there
+    /// once more data is appended.
+    pub fn find_sync(&mut self, syncs: &[u32], max_errors: u32) -> Option<u64> {
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        if max_errors == 0 {
+            // A window's sync starts 40 bits in; skip straight to the first block that has one.
+            let start = (self.pos + 40) / 8;
+            let candidate = crate::simd::skip(&self.inner, start, syncs);
+            self.pos = self.pos.max((candidate * 8).saturating_sub(40));
+        }
+
+        let end = self.inner.len() * 8;
+        let mut window = self.window(self.pos)?;
+
+        loop {
+            self.pos += 1;
+            let candidate = window as u32 & 0xffffff;
+            if syncs
+                .iter()
+                .any(|sync| (candidate ^ sync).count_ones() <= max_errors)
+            {
+                return Some(window);
+            }
+
+            let next = self.pos + 63;
+            if next >= end {
+                return None;
+            }
+            let bit = (self.inner[next / 8] >> (7 - next % 8)) & 1;
+            window = (window << 1) | bit as u64;
+        }
+    }
+
+    /// Moves the cursor `n` bits forward, stopping at the end of the buffered data.
+    fn advance(&mut self, n: usize) {
+        self.pos = (self.pos + n).min(self.inner.len() * 8);
+    }
+
+    /// Returns the next `n` bits without consuming them, or `None` if fewer are buffered.
+    pub fn peek_bits(&mut self, n: usize) -> Option<BitVec<u8, Msb0>> {
+        let bits = &self.inner.as_bits::<Msb0>()[self.pos..];
+        if bits.len() < n {
+            return None;
+        }
+
+        Some(bits[..n].to_bitvec())
+    }
+}
+
+impl Iterator for BitIterator {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let number = self.window(self.pos)?;
+        self.pos += 1;
+
+        Some(number)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let end = (self.inner.len() * 8).saturating_sub(63);
+        self.pos = (self.pos + n).min(end);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.inner.len() * 8).saturating_sub(self.pos + 63);
+        (len, Some(len))
+    }
+}
+
+/// Counts the windows left, i.e. the bits from the cursor that still have 63 bits after them.
+impl ExactSizeIterator for BitIterator {}
+
+/// Whether a channel is currently receiving decodable frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lock {
+    /// Not enough consecutive CRC-valid frames yet: mistuned, or nothing is transmitting.
+    Searching,
+    /// Frames are passing their CRC.
+    Locked,
+}
+
+/// Hysteresis for [`Lock`]: `acquire` consecutive valid frames lock the channel, `release`
+/// consecutive CRC failures drop it again.
+#[derive(Debug, Clone)]
+struct LockTracker {
+    state: Lock,
+    acquire: u32,
+    release: u32,
+    /// Length of the current run of valid (when searching) or failed (when locked) frames.
+    run: u32,
+}
+
+impl LockTracker {
+    fn new(acquire: u32, release: u32) -> Self {
+        Self {
+            state: Lock::Searching,
+            acquire,
+            release,
+            run: 0,
+        }
+    }
+
+    fn record(&mut self, valid: bool) {
+        match (self.state, valid) {
+            (Lock::Searching, true) | (Lock::Locked, false) => self.run += 1,
+            _ => self.run = 0,
+        }
+
+        match self.state {
+            Lock::Searching if self.run >= self.acquire => {
+                self.state = Lock::Locked;
+                self.run = 0;
+            }
+            Lock::Locked if self.run >= self.release => {
+                self.state = Lock::Searching;
+                self.run = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ChannelState {
+    Header,
+    /// A sync window starting at bit `start` was found; the A-field follows it. `fp` tells
+    /// whether the sync was closer to the fixed part pattern, `sync_errors` how many bits it
+    /// differed from it.
+    Payload {
+        start: u64,
+        fp: bool,
+        sync_errors: u32,
+    },
+    PayloadB {
+        bytes: [u8; 8],
+        corrected: bool,
+    },
+}
+
+/// Turns a bit stream into [`Packet`]s: hunts for sync words, checks the A-field and reads the
+/// B-field.
+#[derive(Debug)]
+pub struct Decoder {
+    bits: BitIterator,
+    state: ChannelState,
+    syncs: SyncWords,
+    sync_errors: u32,
+    correct_errors: bool,
+    frame_timing: bool,
+    /// Start of the sync window of the last frame that passed its CRC.
+    last_frame: Option<u64>,
+    /// Start of the sync window of the burst being decoded.
+    burst_start: u64,
+    burst_time: Option<(Frame, Slot)>,
+    timing: Timing,
+    errors: ErrorStats,
+    /// Overflowed input already reported in a gap.
+    reported_overflow: u64,
+    /// A packet to return before decoding further.
+    pending: Option<Packet>,
+    lock: LockTracker,
+}
+
+impl Decoder {
+    /// Decodes `bits` with exact sync matching and no error correction.
+    pub fn new(bits: BitIterator) -> Self {
+        Self {
+            bits,
+            state: ChannelState::Header,
+            syncs: SyncWords::DECT,
+            sync_errors: 0,
+            correct_errors: false,
+            frame_timing: false,
+            last_frame: None,
+            burst_start: 0,
+            burst_time: None,
+            timing: Timing::default(),
+            errors: ErrorStats::default(),
+            reported_overflow: 0,
+            pending: None,
+            lock: LockTracker::new(3, 4),
+        }
+    }
+
+    /// Sets how many consecutive CRC-valid frames acquire [`Lock::Locked`] and how many
+    /// consecutive failures drop it (3 and 4 by default).
+    pub fn with_lock_thresholds(mut self, acquire: u32, release: u32) -> Self {
+        self.lock = LockTracker::new(acquire, release);
+        self
+    }
+
+    /// Error counts since the decoder was created.
+    pub fn error_stats(&self) -> ErrorStats {
+        self.errors
+    }
+
+    /// Whether frames are currently decoding.
+    pub fn lock(&self) -> Lock {
+        self.lock.state
+    }
+
+    /// Hunts for `syncs` instead of the standard DECT patterns. Only their low 24 bits are used.
+    pub fn with_sync_words(mut self, syncs: SyncWords) -> Self {
+        self.syncs = SyncWords {
+            fp: syncs.fp & 0xffffff,
+            pp: syncs.pp & 0xffffff,
+        };
+        self
+    }
+
+    /// The sync words hunted for.
+    pub fn sync_words(&self) -> SyncWords {
+        self.syncs
+    }
+
+    /// Accepts sync words with up to `errors` flipped bits.
+    pub fn with_sync_errors(mut self, errors: u32) -> Self {
+        self.sync_errors = errors;
+        self
+    }
+
+    /// Repairs A-fields with a single bit error instead of dropping them.
+    pub fn with_error_correction(mut self, enabled: bool) -> Self {
+        self.correct_errors = enabled;
+        self
+    }
+
+    /// Rejects inexact sync matches that fall outside the slot grid of the last good frame.
+    ///
+    /// This assumes the input is a continuous bit stream at 1.152 Mbit/s, so bit offsets
+    /// translate directly into time. Exact matches are always accepted, as are all matches when
+    /// no frame has been seen for a multiframe.
+    pub fn with_frame_timing(mut self, enabled: bool) -> Self {
+        self.frame_timing = enabled;
+        self
+    }
+
+    /// Bit offset in the input of the S-field of the burst the last packet belongs to.
+    ///
+    /// Counts every bit ever fed in, including any dropped under the buffer limit.
+    pub fn burst_position(&self) -> u64 {
+        // The sync word ends the 64-bit window, and the S-field starts 8 bits before it.
+        self.burst_start + 32
+    }
+
+    /// The slot of the burst the last packet belongs to, if it could be inferred.
+    ///
+    /// Like [`with_frame_timing`](Self::with_frame_timing) this assumes a continuous bit stream
+    /// at 1.152 Mbit/s. A slot is known once a burst has passed its CRC within the last
+    /// multiframe.
+    pub fn burst_slot(&self) -> Option<Slot> {
+        self.burst_time.map(|(_, slot)| slot)
+    }
+
+    /// The frame of the burst the last packet belongs to, if it could be inferred. Known under
+    /// the same conditions as [`burst_slot`](Self::burst_slot).
+    pub fn burst_frame(&self) -> Option<Frame> {
+        self.burst_time.map(|(frame, _)| frame)
+    }
+
+    /// Whether a sync window starting at `start` lines up with the expected slot boundaries.
+    fn on_grid(&self, start: u64) -> bool {
+        let last = match self.last_frame {
+            Some(last) if start - last <= GRID_LIFETIME => last,
+            _ => return true,
+        };
+
+        let phase = (start - last) % SLOT_BITS;
+        phase.min(SLOT_BITS - phase) <= SLOT_TOLERANCE
+    }
+
+    /// Checks the R-CRC of an A-field, repairing a single bit error if enabled.
+    ///
+    /// Returns whether a bit was corrected.
+    fn check_a_field(&self, bytes: &mut [u8; 8]) -> Result<bool> {
+        let syndrome = bytes.crc();
+        if syndrome == 0 {
+            return Ok(false);
+        }
+        if self.correct_errors && crc::correct_single_bit(bytes).is_some() {
+            return Ok(true);
+        }
+        Err(DecodeError::Crc { syndrome })
+    }
+
+    /// Records that `bits` of input were lost, e.g. datagrams dropped before they reached the
+    /// decoder. A partial burst still buffered cannot continue into what comes next, so it is
+    /// dropped too, and both are reported as a dropped [`Packet::Gap`].
+    pub fn discard(&mut self, bits: u64) {
+        self.bits.discard(bits);
+        self.state = ChannelState::Header;
+    }
+
+    /// Decodes the next packet from the buffered input.
+    ///
+    /// Returns `Ok(None)` when more input is needed.
+    pub async fn parse(&mut self) -> Result<Option<Packet>> {
+        self.next_packet()
+    }
+
+    /// Appends `data` to the input and decodes every packet that is complete.
+    ///
+    /// The blocking counterpart of [`parse`](Self::parse); no async runtime needed. `data` may be
+    /// larger than the buffer limit: it is appended a piece at a time, decoding in between.
+    pub fn push(&mut self, mut data: &[u8]) -> Result<Vec<Packet>> {
+        let mut packets = Vec::new();
+        loop {
+            while let Some(packet) = self.next_packet()? {
+                packets.push(packet);
+            }
+            if data.is_empty() {
+                return Ok(packets);
+            }
+            let (piece, rest) = data.split_at(self.bits.room().clamp(1, data.len()));
+            self.extend(piece.iter().copied());
+            data = rest;
+        }
+    }
+
+    pub(crate) fn next_packet(&mut self) -> Result<Option<Packet>> {
+        let overflowed = self.bits.overflowed() - self.reported_overflow;
+        if overflowed > 0 {
+            self.reported_overflow += overflowed;
+            return Ok(Some(Packet::Gap {
+                bits: overflowed,
+                dropped: true,
+            }));
+        }
+        if let Some(packet) = self.pending.take() {
+            return Ok(Some(packet));
+        }
+
+        loop {
+            match self.state {
+                ChannelState::Header => {
+                    let SyncWords { fp, pp } = self.syncs;
+                    let sync = self.bits.find_sync(&[fp, pp], self.sync_errors);
+
+                    let sync = match sync {
+                        Some(index) => index,
+                        None => return Ok(None),
+                    };
+                    let start = self.bits.position() - 1;
+                    let candidate = sync as u32 & 0xffffff;
+
+                    let exact = candidate == fp || candidate == pp;
+                    if self.frame_timing && !exact && !self.on_grid(start) {
+                        continue;
+                    }
+
+                    let fp_errors = (candidate ^ fp).count_ones();
+                    let pp_errors = (candidate ^ pp).count_ones();
+                    self.state = ChannelState::Payload {
+                        start,
+                        fp: fp_errors <= pp_errors,
+                        sync_errors: fp_errors.min(pp_errors),
+                    };
+                    self.burst_start = start;
+                    self.burst_time = self.timing.infer(start);
+                    return Ok(Some(Packet::Header {
+                        rxmode: 0,
+                        channel: 0,
+                        slot: 0,
+                        frameno: 0,
+                        rssi: 0,
+                        preamble: [
+                            (sync >> 40 & 0xff) as u8,
+                            (sync >> 32 & 0xff) as u8,
+                            (sync >> 24 & 0xff) as u8,
+                        ],
+                        sync: (sync as u16).to_be(),
+                    }));
+                }
+                ChannelState::Payload {
+                    start,
+                    fp,
+                    sync_errors,
+                } => {
+                    // The cursor sits one bit past the start of the sync window, so the A-field
+                    // starts 63 bits further on. Nothing is consumed until its CRC checks out.
+                    let data = match self.bits.window(self.bits.pos + 63) {
+                        Some(data) => data,
+                        None => return Ok(None),
+                    };
+
+                    let mut bytes = data.to_be_bytes();
+
+                    let corrected = match self.check_a_field(&mut bytes) {
+                        Ok(corrected) => corrected,
+                        Err(_) => {
+                            self.lock.record(false);
+                            self.errors.crc_failures += 1;
+                            // Most likely a false sync. Resume the hunt one bit after it, so a
+                            // real frame starting within the bits we just looked at is not
+                            // skipped.
+                            self.state = ChannelState::Header;
+                            continue;
+                        }
+                    };
+                    self.bits.advance(63 + 64);
+                    let gap = self
+                        .last_frame
+                        .map(|last| start - last)
+                        .filter(|&bits| bits > GRID_LIFETIME)
+                        .map(|bits| Packet::Gap {
+                            bits,
+                            dropped: false,
+                        });
+                    self.last_frame = Some(start);
+                    self.lock.record(true);
+                    self.errors.good += 1;
+                    self.errors.bits += 24 + 64;
+                    self.errors.bit_errors += u64::from(sync_errors) + u64::from(corrected);
+                    self.burst_time = Some(self.timing.anchor(start, fp, &bytes));
+
+                    if b_field_type(bytes[0]) == BA_NO_B_FIELD {
+                        self.state = ChannelState::Header;
+                        let packet = a_packet(bytes, corrected, None);
+                        return Ok(Some(match gap {
+                            Some(gap) => {
+                                self.pending = Some(packet);
+                                gap
+                            }
+                            None => packet,
+                        }));
+                    }
+
+                    self.state = ChannelState::PayloadB { bytes, corrected };
+                    if gap.is_some() {
+                        return Ok(gap);
+                    }
+                }
+                ChannelState::PayloadB { bytes, corrected } => {
+                    let Some(b) = self.bits.peek_bits(B_FIELD_BITS) else {
+                        // We need more data
+                        return Ok(None);
+                    };
+                    self.bits.advance(B_FIELD_BITS);
+                    self.state = ChannelState::Header;
+                    return Ok(Some(a_packet(bytes, corrected, Some(b))));
+                }
+            }
+        }
+    }
+}
+
+/// TA: what the A-field tail carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TailType {
+    /// C-channel data, packet 0.
+    Ct0 = 0,
+    /// C-channel data, packet 1.
+    Ct1 = 1,
+    /// Identities (RFPI or PMID) sent with connectionless C-channel data.
+    NtConnectionless = 2,
+    /// Identities: the RFPI from a fixed part, the PMID from a portable part.
+    Nt = 3,
+    /// System information.
+    Qt = 4,
+    /// Escape.
+    Escape = 5,
+    /// MAC control.
+    Mt = 6,
+    /// Paging from a fixed part, MAC control in the first portable part transmission.
+    Pt = 7,
+}
+
+impl TailType {
+    /// The tail type an A-field header announces.
+    pub fn from_header(header: u8) -> Self {
+        match header >> 5 {
+            0 => Self::Ct0,
+            1 => Self::Ct1,
+            2 => Self::NtConnectionless,
+            3 => Self::Nt,
+            4 => Self::Qt,
+            5 => Self::Escape,
+            6 => Self::Mt,
+            _ => Self::Pt,
+        }
+    }
+}
+
+/// The short name (`Nt`), or with `{:#}` what the tail carries (`identities`).
+impl fmt::Display for TailType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, description) = match self {
+            Self::Ct0 => ("Ct0", "C-channel data, packet 0"),
+            Self::Ct1 => ("Ct1", "C-channel data, packet 1"),
+            Self::NtConnectionless => ("NtCL", "identities, connectionless"),
+            Self::Nt => ("Nt", "identities"),
+            Self::Qt => ("Qt", "system information"),
+            Self::Escape => ("Esc", "escape"),
+            Self::Mt => ("Mt", "MAC control"),
+            Self::Pt => ("Pt", "paging"),
+        };
+        f.write_str(if f.alternate() { description } else { name })
+    }
+}
+
+/// The BA bits of an A-field header: what the B-field holds, or [`BA_NO_B_FIELD`].
+pub fn b_field_type(header: u8) -> u8 {
+    (header >> 1) & 7
+}
+
+/// The tail of an A-field carrying Q-channel system information.
+fn qt_tail(a_field: &[u8; 8]) -> Option<&[u8]> {
+    const TA_QT: u8 = 4;
+    (a_field[0] >> 5 == TA_QT).then_some(&a_field[1..6])
+}
+
+/// The slot number a fixed part broadcasts in a Qt static system information tail.
+fn qt_slot_number(tail: &[u8]) -> Option<u8> {
+    // Qt header 0000 or 0001, then NR (1 bit) and SN (4 bits).
+    if tail[0] >> 5 != 0 {
+        return None;
+    }
+    let number = (tail[0] & 0x07) << 1 | tail[1] >> 7;
+    (number < 12).then_some(number)
+}
+
+/// The 24-bit number of the current multiframe from a Qt multiframe number tail.
+fn qt_multiframe_number(tail: &[u8]) -> Option<u32> {
+    // Qt header 0110 and 12 spare bits.
+    (tail[0] >> 4 == 0b0110)
+        .then(|| u32::from(tail[2]) << 16 | u32::from(tail[3]) << 8 | u32::from(tail[4]))
+}
+
+/// Splits a CRC-checked A-field (header, tail, R-CRC) into a packet.
+fn a_packet(bytes: [u8; 8], corrected: bool, b: Option<BitVec<u8, Msb0>>) -> Packet {
+    Packet::A {
+        header: bytes[0],
+        tail: [bytes[1], bytes[2], bytes[3], bytes[4], bytes[5]],
+        crc: u16::from_be_bytes([bytes[6], bytes[7]]),
+        b,
+        corrected,
+    }
+}
+
+/// Decodes every packet in a complete capture with the default decoder settings.
+pub fn decode_all(bytes: &[u8]) -> Result<Vec<Packet>> {
+    let mut decoder = Decoder::new(BitIterator::new(bytes));
+
+    let mut packets = Vec::new();
+    while let Some(packet) = decoder.next_packet()? {
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
+impl Extend<u8> for Decoder {
+    fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
+        self.bits.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bitvec::{
+        bitvec,
+        field::BitField,
+        order::Msb0,
+        view::{AsBits, AsMutBits},
+    };
+    use proptest::{collection::vec, prelude::*};
+
+    use super::{
+        decode_all, BitIterator, DecodeError, Decoder, Lock, LockTracker, Packet, SyncWords,
+        FP_SYNC, PP_SYNC,
+    };
+    use crate::{
+        encode::{AField, Burst},
+        synth::{single_burst, Synth},
+    };
+
+    #[test]
+    fn test_bit_iterator() {
+        let (data, _) = single_burst();
+        let iter = super::BitIterator::new(data);
+
+        assert!(iter
+            .map(|n| (n & 0xffffff) as u32)
+            .any(|n| n == super::FP_SYNC || n == super::PP_SYNC));
+    }
+
+    #[test]
+    fn test_bit_iterator_windows() {
+        let (data, _) = single_burst();
+        let bits = data.as_bits::<Msb0>();
+        for (pos, window) in BitIterator::new(&data).enumerate() {
+            assert_eq!(window, bits[pos..pos + 64].load_be::<u64>());
+        }
+    }
+
+    fn assert_find_sync_matches(data: &[u8]) -> usize {
+        let is_sync =
+            |n: &u64| (*n & 0xffffff) as u32 == FP_SYNC || (*n & 0xffffff) as u32 == PP_SYNC;
+
+        let mut expected = BitIterator::new(data);
+        let mut iter = BitIterator::new(data);
+        let mut found = 0;
+        loop {
+            let sync = iter.find_sync(&[FP_SYNC, PP_SYNC], 0);
+            assert_eq!(sync, expected.find(is_sync));
+            assert_eq!(iter.pos, expected.pos);
+            if sync.is_none() {
+                return found;
+            }
+            found += 1;
+        }
+    }
+
+    #[test]
+    fn test_find_sync() {
+        let (data, _) = single_burst();
+        assert_eq!(assert_find_sync_matches(&data), 1);
+
+        // Syncs at every bit alignment, some closer together than a SIMD block.
+        let mut data = Synth::new(2).with_occupancy(0.0).generate().into_bytes();
+        let bits = data.as_mut_bits::<Msb0>();
+        let mut pos = 40;
+        for i in 0..100 {
+            let sync = if i % 2 == 0 { FP_SYNC } else { PP_SYNC };
+            bits[pos..pos + 24].store_be(sync);
+            pos += 24 + i % 37;
+        }
+        assert_eq!(assert_find_sync_matches(&data), 100);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_len_counts_windows(data in vec(any::<u8>(), 0..64), skip in 0usize..600) {
+            let mut iter = BitIterator::new(&data);
+            iter.nth(skip);
+
+            let len = iter.len();
+            prop_assert_eq!(len, iter.count());
+        }
+
+        #[test]
+        fn prop_peek_then_nth_consumes_n(
+            data in vec(any::<u8>(), 8..64),
+            start in 0usize..256,
+            n in 1usize..256,
+        ) {
+            let bits = data.as_bits::<Msb0>();
+            let mut iter = BitIterator::new(&data);
+            iter.advance(start);
+            let pos = iter.position() as usize;
+
+            let peeked = iter.peek_bits(n);
+            prop_assert_eq!(iter.position() as usize, pos);
+            prop_assert_eq!(peeked.is_some(), pos + n <= bits.len());
+            if let Some(peeked) = peeked {
+                prop_assert_eq!(peeked, bits[pos..pos + n].to_bitvec());
+            }
+
+            if pos + n + 63 <= bits.len() {
+                let window = iter.nth(n - 1).unwrap();
+                prop_assert_eq!(iter.position() as usize, pos + n);
+                prop_assert_eq!(window, bits[pos + n - 1..pos + n + 63].load_be::<u64>());
+            } else {
+                prop_assert_eq!(iter.nth(n - 1), None);
+                prop_assert_eq!(iter.len(), 0);
+            }
+        }
+
+        #[test]
+        fn prop_chunked_input_matches_whole(
+            data in vec(any::<u8>(), 0..256),
+            chunk in 1usize..32,
+            step in 1usize..100,
+        ) {
+            let mut whole = BitIterator::new(&data);
+            let mut chunked = BitIterator::new([]);
+            for piece in data.chunks(chunk) {
+                chunked.extend(piece.iter().copied());
+                // A failed `nth` still skips what it could, so only step when `len` says the
+                // whole step is buffered.
+                while chunked.len() >= step {
+                    let window = chunked.nth(step - 1);
+                    prop_assert!(window.is_some());
+                    prop_assert_eq!(window, whole.nth(step - 1));
+                    prop_assert_eq!(chunked.position(), whole.position());
+                }
+            }
+            prop_assert_eq!(chunked.len(), whole.len());
+        }
+
+        #[test]
+        fn prop_find_sync_matches_find(data in vec(any::<u8>(), 0..256), sync in any::<u32>()) {
+            // Plant a sync so the search has something to find.
+            let mut data = data;
+            let sync = sync & 0xffffff;
+            if data.len() >= 8 {
+                let at = data.len() / 2 - 4;
+                data.as_mut_bits::<Msb0>()[at * 8 + 40..at * 8 + 64].store_be(sync);
+            }
+
+            let mut iter = BitIterator::new(&data);
+            let mut expected = BitIterator::new(&data);
+            loop {
+                let found = iter.find_sync(&[sync], 0);
+                prop_assert_eq!(found, expected.find(|n| (*n & 0xffffff) as u32 == sync));
+                prop_assert_eq!(iter.position(), expected.position());
+                if found.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bit_iterator_compaction() {
+        let data = Synth::new(3).generate().into_bytes();
+        let mut iter = BitIterator::new(&data[..64]);
+        let windows = BitIterator::new(&data).collect::<Vec<_>>();
+
+        let mut seen = Vec::new();
+        for chunk in data[64..].chunks(100) {
+            seen.extend(iter.by_ref());
+            iter.extend(chunk.iter().copied());
+            assert!(iter.inner.len() <= 8 + chunk.len());
+            assert_eq!(iter.position(), seen.len() as u64);
+        }
+        seen.extend(iter);
+
+        assert_eq!(seen, windows);
+    }
+
+    #[test]
+    fn test_bit_iterator_limit() {
+        let mut iter = BitIterator::new([]).with_limit(16);
+        let (data, _) = single_burst();
+        iter.extend(data.iter().copied());
+        assert_eq!(iter.inner, &data[data.len() - 16..]);
+        assert_eq!(iter.overflowed(), (data.len() as u64 - 16) * 8);
+        assert_eq!(iter.count(), 16 * 8 - 63);
+    }
+
+    #[test]
+    fn test_lock_hysteresis() {
+        let mut lock = LockTracker::new(3, 2);
+        for valid in [true, true, false, true, true] {
+            lock.record(valid);
+            assert_eq!(lock.state, Lock::Searching);
+        }
+        lock.record(true);
+        assert_eq!(lock.state, Lock::Locked);
+
+        for valid in [false, true, false] {
+            lock.record(valid);
+            assert_eq!(lock.state, Lock::Locked);
+        }
+        lock.record(false);
+        assert_eq!(lock.state, Lock::Searching);
+    }
+
+    #[tokio::test]
+    async fn test_decoder() {
+        let (data, planted) = single_burst();
+        let mut decoder = Decoder::new(BitIterator::new(&data));
+
+        let Some(Packet::Header { preamble, sync, .. }) = decoder.parse().await.unwrap() else {
+            panic!("expected the burst's sync word first");
+        };
+        // Only the last byte is the burst's own preamble; the rest is whatever came before.
+        assert_eq!(preamble[2], 0xaa);
+        assert_eq!(u16::from_be(sync), FP_SYNC as u16);
+        assert_eq!(decoder.burst_position(), planted.position as u64);
+
+        let Some(Packet::A {
+            header,
+            tail,
+            crc,
+            b: Some(b),
+            corrected: false,
+        }) = decoder.parse().await.unwrap()
+        else {
+            panic!("expected the A-field after the sync word");
+        };
+        let sent = planted.burst.a_field().to_bytes();
+        assert_eq!(header, sent[0]);
+        assert_eq!(tail[..], sent[1..6]);
+        assert_eq!(crc.to_be_bytes(), sent[6..]);
+        assert_eq!(b.len(), super::B_FIELD_BITS);
+        assert_eq!(decoder.burst_position(), planted.position as u64);
+
+        // One burst, so nothing more.
+        assert!(decoder.parse().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_decoder_error_correction() {
+        // Flip one bit in the tail of the only A-field in the capture.
+        let (mut data, planted) = single_burst();
+        let bits = data.as_mut_bits::<Msb0>();
+        let bit = planted.a_field_position() + 20;
+        let flipped = !bits[bit];
+        bits.set(bit, flipped);
+
+        let mut decoder = Decoder::new(BitIterator::new(&data));
+        assert!(matches!(
+            decoder.parse().await.unwrap(),
+            Some(Packet::Header { .. })
+        ));
+        assert!(decoder.parse().await.unwrap().is_none());
+
+        let mut decoder = Decoder::new(BitIterator::new(&data)).with_error_correction(true);
+        assert!(matches!(
+            decoder.parse().await.unwrap(),
+            Some(Packet::Header { .. })
+        ));
+        assert!(matches!(
+            decoder.parse().await.unwrap(),
+            Some(Packet::A {
+                corrected: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_check_a_field() {
+        let good = 0x6110_2af1_2c0d_3cd5u64.to_be_bytes();
+        let decoder = Decoder::new(BitIterator::new([]));
+        assert!(matches!(
+            decoder.check_a_field(&mut good.clone()),
+            Ok(false)
+        ));
+
+        let mut bad = good;
+        bad[3] ^= 0x10;
+        assert!(matches!(
+            decoder.check_a_field(&mut bad.clone()),
+            Err(DecodeError::Crc { .. })
+        ));
+
+        let decoder = decoder.with_error_correction(true);
+        assert!(matches!(decoder.check_a_field(&mut bad), Ok(true)));
+        assert_eq!(bad, good);
+    }
+
+    #[test]
+    fn test_decoder_push() {
+        let (data, planted) = single_burst();
+        let mut decoder = Decoder::new(BitIterator::new([]));
+        let mut packets = Vec::new();
+        for chunk in data.chunks(100) {
+            packets.extend(decoder.push(chunk).unwrap());
+        }
+
+        let [Packet::Header { .. }, Packet::A {
+            header,
+            tail,
+            crc,
+            b: Some(b),
+            ..
+        }] = &packets[..]
+        else {
+            panic!("unexpected packets: {packets:?}");
+        };
+        // Header first, then the RFPI as tail, then the R-CRC.
+        let sent = planted.burst.a_field().to_bytes();
+        assert_eq!(*header, sent[0]);
+        assert_eq!(tail[..], sent[1..6]);
+        assert_eq!(crc.to_be_bytes(), sent[6..]);
+        assert_eq!(b.len(), super::B_FIELD_BITS);
+    }
+
+    #[test]
+    fn test_decoder_push_large() {
+        // More than the buffer limit in one call: nothing may be discarded as overflow.
+        let slots = super::DEFAULT_BUFFER_LIMIT * 8 / super::SLOT_BITS as usize + 24;
+        let capture = Synth::new(5).with_slots(slots).generate();
+        let bursts = capture.bursts.len();
+        let bytes = capture.into_bytes();
+        assert!(bytes.len() > super::DEFAULT_BUFFER_LIMIT);
+
+        let mut decoder = Decoder::new(BitIterator::new([]));
+        let packets = decoder.push(&bytes).unwrap();
+        assert!(!packets.iter().any(|p| matches!(p, Packet::Gap { .. })));
+        let a_fields = packets
+            .iter()
+            .filter(|p| matches!(p, Packet::A { .. }))
+            .count();
+        assert_eq!(a_fields, bursts);
+    }
+
+    #[test]
+    fn test_decode_all() {
+        // Larger than the default buffer limit, which must not apply to a complete capture.
+        let slots = super::DEFAULT_BUFFER_LIMIT * 8 / super::SLOT_BITS as usize + 24;
+        let capture = Synth::new(4).with_slots(slots).generate();
+        let bursts = capture.bursts.len();
+        let packets = decode_all(&capture.into_bytes()).unwrap();
+
+        let a_fields = packets
+            .iter()
+            .filter(|p| matches!(p, Packet::A { .. }))
+            .count();
+        assert_eq!(a_fields, bursts);
+    }
+
+    #[tokio::test]
+    async fn test_decoder_bit_slip() {
+        // A false FP sync 30 bits ahead of the real one: the real sync lies inside the bits the
+        // false one would have taken as its A-field.
+        let (mut data, planted) = single_burst();
+        let sync = planted.sync_position();
+        let bits = data.as_mut_bits::<Msb0>();
+        bits[sync - 30..sync - 6].store_be(FP_SYNC);
+
+        let mut decoder = Decoder::new(BitIterator::new(&data));
+        let mut packets = Vec::new();
+        while let Some(packet) = decoder.parse().await.unwrap() {
+            packets.push(packet);
+        }
+
+        assert!(matches!(
+            packets[..],
+            [
+                Packet::Header { .. },
+                Packet::Header { .. },
+                Packet::A { b: Some(_), .. }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decoder_frame_timing() {
+        // Two syncs with one bit error after the real frame: one a slot later, one off the grid.
+        let (mut data, planted) = single_burst();
+        let sync = planted.sync_position();
+        let bits = data.as_mut_bits::<Msb0>();
+        bits[sync + 480..sync + 480 + 24].store_be(FP_SYNC ^ 0x10);
+        bits[sync + 700..sync + 700 + 24].store_be(FP_SYNC ^ 0x10);
+
+        let count_headers = |mut decoder: Decoder| async move {
+            let mut headers = 0;
+            while let Some(packet) = decoder.parse().await.unwrap() {
+                if let Packet::Header { .. } = packet {
+                    headers += 1;
+                }
+            }
+            headers
+        };
+
+        let decoder = Decoder::new(BitIterator::new(&data)).with_sync_errors(1);
+        assert_eq!(count_headers(decoder).await, 3);
+
+        let decoder = Decoder::new(BitIterator::new(&data))
+            .with_sync_errors(1)
+            .with_frame_timing(true);
+        assert_eq!(count_headers(decoder).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_decoder_sync_errors() {
+        // Flip two bits of the only sync word in the capture.
+        let (mut data, planted) = single_burst();
+        let sync = planted.sync_position();
+        let bits = data.as_mut_bits::<Msb0>();
+        for bit in [sync + 3, sync + 17] {
+            let flipped = !bits[bit];
+            bits.set(bit, flipped);
+        }
+
+        let mut decoder = Decoder::new(BitIterator::new(&data));
+        assert!(decoder.parse().await.unwrap().is_none());
+
+        let mut decoder = Decoder::new(BitIterator::new(&data)).with_sync_errors(2);
+        assert!(matches!(
+            decoder.parse().await.unwrap(),
+            Some(Packet::Header { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decoder_sync_words() {
+        // A burst with a non-standard S-field is only found when its pattern is configured.
+        let (mut data, planted) = single_burst();
+        let sync = planted.sync_position();
+        data.as_mut_bits::<Msb0>()[sync..sync + 24].store_be(0x123456_u32);
+
+        let mut decoder = Decoder::new(BitIterator::new(&data));
+        assert!(decoder.parse().await.unwrap().is_none());
+
+        let syncs = SyncWords {
+            fp: 0x123456,
+            ..SyncWords::DECT
+        };
+        let mut decoder = Decoder::new(BitIterator::new(&data)).with_sync_words(syncs);
+        assert!(matches!(
+            decoder.parse().await.unwrap(),
+            Some(Packet::Header { .. })
+        ));
+        assert!(matches!(
+            decoder.parse().await.unwrap(),
+            Some(Packet::A { .. })
+        ));
+    }
+
+    #[test]
+    fn test_slot_inference() {
+        // Two FP bursts, a PP burst in the duplex slot of the first, then a Qt broadcast giving
+        // the real slot number of the second.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        let mut burst = |slot: usize, burst: Burst| {
+            bits.resize(480 + slot * 480, false);
+            burst.write(&mut bits);
+        };
+        burst(0, Burst::fp(AField::nt([1; 5])));
+        burst(3, Burst::fp(AField::nt([1; 5])));
+        burst(12, Burst::pp(AField::mt(0, 0)));
+        burst(27, Burst::fp(AField::qt(0, 7 << 31)));
+        burst(28, Burst::fp(AField::nt([1; 5])));
+        bits.resize(bits.len() + 480, false);
+
+        let mut decoder = Decoder::new(BitIterator::new(bits.as_raw_slice()));
+        let mut slots = Vec::new();
+        let mut frames = Vec::new();
+        while let Some(packet) = decoder.next_packet().unwrap() {
+            if let Packet::A { .. } = packet {
+                let slot = decoder.burst_slot().unwrap();
+                slots.push((slot.number, slot.confirmed));
+                let frame = decoder.burst_frame().unwrap();
+                frames.push((frame.multiframe, frame.number, frame.confirmed));
+            }
+        }
+        // The Qt broadcast also pins the frame number.
+        assert_eq!(
+            frames,
+            [
+                (None, 0, false),
+                (None, 0, false),
+                (None, 0, false),
+                (None, 8, true),
+                (None, 8, true)
+            ]
+        );
+        assert_eq!(
+            slots,
+            [(0, false), (3, false), (12, false), (7, true), (8, true)]
+        );
+    }
+
+    #[test]
+    fn test_frame_inference() {
+        // A multiframe number broadcast, then FP bursts in the same slot of later frames.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        let mut burst = |frame: usize, burst: Burst| {
+            bits.resize(480 + frame * 24 * 480, false);
+            burst.write(&mut bits);
+        };
+        burst(0, Burst::fp(AField::qt(0b0110, 0xf0f << 24 | 0xff_ffff)));
+        for frame in [1, 7, 8, 16] {
+            burst(frame, Burst::fp(AField::nt([1; 5])));
+        }
+        bits.resize(bits.len() + 480, false);
+
+        let mut decoder = Decoder::new(BitIterator::new(bits.as_raw_slice()));
+        let mut frames = Vec::new();
+        while let Some(packet) = decoder.next_packet().unwrap() {
+            if let Packet::A { .. } = packet {
+                let frame = decoder.burst_frame().unwrap();
+                assert!(frame.confirmed);
+                frames.push((frame.multiframe.unwrap(), frame.number));
+            }
+        }
+        // The multiframe number wraps at 24 bits.
+        assert_eq!(
+            frames,
+            [
+                (0xff_ffff, 8),
+                (0xff_ffff, 9),
+                (0xff_ffff, 15),
+                (0, 0),
+                (0, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timing_track() {
+        // A multiframe number broadcast, bursts from both sides, then silence for 20 frames.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        let mut burst = |slot: usize, burst: Burst| {
+            bits.resize(480 + slot * 480, false);
+            burst.write(&mut bits);
+        };
+        burst(0, Burst::fp(AField::qt(0b0110, 0x123)));
+        burst(24 + 2, Burst::fp(AField::nt([1; 5])));
+        burst(24 + 14, Burst::pp(AField::nt([2; 5])));
+        burst(22 * 24 + 2, Burst::fp(AField::nt([1; 5])));
+        bits.resize(bits.len() + 480, false);
+
+        // Replaying the packets gives the times and gaps the decoder worked out.
+        let mut decoder = Decoder::new(BitIterator::new(bits.as_raw_slice()));
+        let mut timing = super::Timing::default();
+        let mut gaps = Vec::new();
+        let mut tracked_gaps = Vec::new();
+        while let Some(packet) = decoder.next_packet().unwrap() {
+            let packet = super::DecodedPacket {
+                channel: 0,
+                carrier: None,
+                position: decoder.burst_position(),
+                frame: decoder.burst_frame(),
+                slot: decoder.burst_slot(),
+                received: None,
+                syncs: decoder.sync_words(),
+                packet,
+            };
+            match packet.packet {
+                Packet::Gap { bits, .. } => {
+                    gaps.push(bits);
+                    continue;
+                }
+                Packet::A { .. } => tracked_gaps.extend(timing.gap(packet.position)),
+                Packet::Header { .. } => {}
+            }
+            let time = timing.track(&packet);
+            assert_eq!(time.map(|(frame, _)| frame), packet.frame);
+            assert_eq!(time.map(|(_, slot)| slot), packet.slot);
+        }
+        assert_eq!(gaps, [21 * super::FRAME_BITS - 12 * super::SLOT_BITS]);
+        assert_eq!(tracked_gaps, gaps);
+    }
+
+    #[test]
+    fn test_error_stats() {
+        // Two sync bit errors and one A-field bit error.
+        let (mut data, planted) = single_burst();
+        let bits = data.as_mut_bits::<Msb0>();
+        for bit in [
+            planted.sync_position() + 3,
+            planted.sync_position() + 17,
+            planted.a_field_position() + 30,
+        ] {
+            let flipped = !bits[bit];
+            bits.set(bit, flipped);
+        }
+
+        let mut decoder = Decoder::new(BitIterator::new([]))
+            .with_sync_errors(2)
+            .with_error_correction(true);
+        decoder.push(&data).unwrap();
+
+        let stats = decoder.error_stats();
+        assert_eq!(stats.good, 1);
+        assert_eq!(stats.bits, 88);
+        assert_eq!(stats.bit_errors, 3);
+        assert_eq!(stats.bit_error_rate(), Some(3.0 / 88.0));
+
+        let earlier = stats;
+        decoder.push(&data).unwrap();
+        let interval = decoder.error_stats() - earlier;
+        assert_eq!(interval.good, 1);
+        assert_eq!(interval.bit_errors, 3);
+    }
+
+    #[test]
+    fn test_gaps() {
+        // Bursts in consecutive frames, then silence for 20 frames.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        let mut burst = |frame: usize| {
+            bits.resize(480 + frame * 24 * 480, false);
+            Burst::fp(AField::nt([1; 5])).write(&mut bits);
+        };
+        for frame in [0, 1, 21] {
+            burst(frame);
+        }
+        bits.resize(bits.len() + 480, false);
+
+        let mut decoder = Decoder::new(BitIterator::new([]));
+        let packets = decoder.push(bits.as_raw_slice()).unwrap();
+        let gaps: Vec<_> = packets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| match p {
+                Packet::Gap { bits, dropped } => Some((i, *bits, *dropped)),
+                _ => None,
+            })
+            .collect();
+        // Header, A, Header, A, Header, Gap, A.
+        assert_eq!(gaps, [(5, 20 * super::FRAME_BITS, false)]);
+        assert!(matches!(packets[6], Packet::A { .. }));
+
+        // Input the decoder could not keep up with.
+        let mut decoder = Decoder::new(BitIterator::new([]).with_limit(64));
+        decoder.extend(bits.as_raw_slice().iter().copied());
+        let packet = decoder.next_packet().unwrap();
+        let Some(Packet::Gap {
+            bits: dropped_bits,
+            dropped: true,
+        }) = packet
+        else {
+            panic!("unexpected packet: {packet:?}");
+        };
+        assert_eq!(dropped_bits, (bits.as_raw_slice().len() as u64 - 64) * 8);
+    }
+
+    #[test]
+    fn test_display() {
+        let (data, planted) = single_burst();
+        let packets = decode_all(&data).unwrap();
+        let [r0, r1, r2, r3, r4] = planted.burst.a_field().to_bytes()[1..6] else {
+            unreachable!()
+        };
+        let tail = format!("{r0:02x}{r1:02x}{r2:02x}{r3:02x}{r4:02x}");
+
+        let header = packets[0].to_string();
+        assert!(header.starts_with("S preamble=") && header.ends_with(" sync=e98a FP"));
+        let a = packets[1].to_string();
+        assert!(a.starts_with(&format!("A Nt tail={tail} crc=")) && a.ends_with(" b=320"));
+        assert_eq!(
+            format!("{:#}", packets[1]).lines().nth(1),
+            Some("  tail type Nt (identities)")
+        );
+
+        let gap = Packet::Gap {
+            bits: 2 * super::FRAME_BITS,
+            dropped: true,
+        };
+        assert_eq!(gap.to_string(), "Gap bits=23040 frames=2 dropped");
+        assert_eq!(format!("{gap:#}"), "Gap\n  23040 bits (2 frames) dropped");
+
+        let syncs = SyncWords {
+            fp: 0x12e98a,
+            pp: 0x123456,
+        };
+        let header = packets[0].display_with(syncs).to_string();
+        assert!(header.ends_with(" sync=e98a FP"));
+        let swapped = SyncWords {
+            fp: PP_SYNC,
+            pp: FP_SYNC,
+        };
+        let header = packets[0].display_with(swapped).to_string();
+        assert!(header.ends_with(" sync=e98a PP"));
+        assert_eq!(SyncWords::DECT.classify(0xe98b), None);
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use anyhow::Result;
+use dectdump::decoder::DecodedPacket;
+use futures_core::Stream;
+use tokio::{
+    net::UdpSocket,
+    sync::mpsc::{self, error::TrySendError},
+    time::{self, Instant},
+};
+
+use crate::{
+    channel::{Channel, Source},
+    queue::Sender,
+};
+
+/// Datagrams buffered for a sender whose channel is busy decoding; more are dropped, as the
+/// network would, and reported as a gap on that channel.
+const SENDER_QUEUE: usize = 64;
+
+/// Senders decoded at once on a port unless set otherwise.
+pub const DEFAULT_MAX_SENDERS: usize = 16;
+
+/// A UDP port shared by several demodulators. Datagrams are split by source address, and every
+/// sender gets a channel of its own, started when its first datagram arrives.
+///
+/// Every channel holds a decoder buffer, so their number is capped: datagrams from further
+/// senders are ignored until one of the others has been idle for a while and is stopped.
+#[derive(Debug)]
+pub struct Demux {
+    socket: UdpSocket,
+    port: u16,
+    peers: HashMap<SocketAddr, Peer>,
+    max_senders: usize,
+    idle_timeout: Duration,
+    /// Datagrams ignored because of `max_senders` since the last report.
+    ignored: u64,
+}
+
+/// A sender with a channel of its own.
+#[derive(Debug)]
+struct Peer {
+    /// Datagrams for the channel, each with the bits dropped just before it.
+    queue: mpsc::Sender<(u64, Vec<u8>)>,
+    /// Bits dropped since the last datagram that got through.
+    dropped: u64,
+    last_seen: Instant,
+    index: usize,
+    name: String,
+}
+
+impl Demux {
+    /// Listens on `port` on all interfaces.
+    pub async fn bind(port: u16) -> io::Result<Self> {
+        let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port);
+        let socket = UdpSocket::bind(addr).await?;
+        let port = socket.local_addr()?.port();
+        Ok(Self {
+            socket,
+            port,
+            peers: HashMap::new(),
+            max_senders: DEFAULT_MAX_SENDERS,
+            idle_timeout: Duration::from_secs(60),
+            ignored: 0,
+        })
+    }
+
+    /// Sets how many senders are decoded at once.
+    pub fn with_max_senders(mut self, max_senders: usize) -> Self {
+        self.max_senders = max_senders;
+        self
+    }
+
+    /// Sets how long a sender may stay silent before its channel is stopped (one minute by
+    /// default). Must not be zero.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Receives until the socket fails or the receiving end is dropped, starting channels with
+    /// `new_channel` and the next free index from `next_index`, shared with other ports.
+    pub async fn run(
+        mut self,
+        next_index: Arc<AtomicUsize>,
+        new_channel: impl Fn(usize, Source, String) -> Channel,
+        tx: Sender<Result<DecodedPacket>>,
+    ) -> io::Result<()> {
+        let mut buf = [0u8; 2048];
+        let mut sweep = time::interval(self.idle_timeout);
+        loop {
+            let (len, from) = tokio::select! {
+                received = self.socket.recv_from(&mut buf) => received?,
+                _ = sweep.tick() => {
+                    self.evict_idle();
+                    if self.ignored > 0 {
+                        eprintln!(
+                            "port {}: ignored {} datagrams from more than {} senders",
+                            self.port, self.ignored, self.max_senders
+                        );
+                        self.ignored = 0;
+                    }
+                    continue;
+                }
+            };
+
+            if !self.peers.contains_key(&from) {
+                if self.peers.len() >= self.max_senders {
+                    self.evict_idle();
+                }
+                if self.peers.len() >= self.max_senders {
+                    self.ignored += 1;
+                    continue;
+                }
+            }
+            let peer = self.peers.entry(from).or_insert_with(|| {
+                let (queue, receiver) = mpsc::channel(SENDER_QUEUE);
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let name = format!("port {} from {from}", self.port);
+                eprintln!("channel {index} ({name}): new sender");
+                let dropped = Arc::new(AtomicU64::new(0));
+                let datagrams = Datagrams {
+                    receiver,
+                    dropped: dropped.clone(),
+                };
+                let channel = new_channel(index, Box::new(datagrams), name.clone());
+                tokio::spawn(channel.with_dropped(dropped).run(tx.clone()));
+                Peer {
+                    queue,
+                    dropped: 0,
+                    last_seen: Instant::now(),
+                    index,
+                    name,
+                }
+            });
+
+            peer.last_seen = Instant::now();
+            match peer.queue.try_send((peer.dropped, buf[..len].to_vec())) {
+                Ok(()) => peer.dropped = 0,
+                Err(TrySendError::Full(_)) => peer.dropped += len as u64 * 8,
+                // Channels only stop early when the receiving end is gone.
+                Err(TrySendError::Closed(_)) => return Ok(()),
+            }
+        }
+    }
+
+    /// Stops the channels of senders that have been silent for longer than the idle timeout.
+    /// They finish decoding what they were sent first.
+    fn evict_idle(&mut self) {
+        let timeout = self.idle_timeout;
+        self.peers.retain(|_, peer| {
+            let idle = peer.last_seen.elapsed() >= timeout;
+            if idle {
+                eprintln!("channel {} ({}): sender went quiet", peer.index, peer.name);
+            }
+            !idle
+        });
+    }
+}
+
+/// The datagrams of one sender, handed over by [`Demux`].
+struct Datagrams {
+    receiver: mpsc::Receiver<(u64, Vec<u8>)>,
+    /// Shared with the channel's decoder stream, which reports the bits as a gap.
+    dropped: Arc<AtomicU64>,
+}
+
+impl Stream for Datagrams {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx).map(|datagram| {
+            let (dropped, datagram) = datagram?;
+            self.dropped.fetch_add(dropped, Ordering::Relaxed);
+            Some(Ok(datagram))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::BTreeSet,
+        net::{Ipv4Addr, SocketAddr},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use dectdump::{
+        decoder::{BitIterator, DecodedPacket, Decoder, Packet},
+        encode::{slots, AField, Burst},
+    };
+    use tokio::{net::UdpSocket, time};
+
+    use super::Demux;
+    use crate::{
+        channel::{Channel, Source},
+        queue::{self, Overflow, Receiver},
+    };
+
+    fn new_channel(index: usize, source: Source, name: String) -> Channel {
+        Channel::new(
+            index,
+            source,
+            name,
+            None,
+            Decoder::new(BitIterator::new([])),
+        )
+    }
+
+    fn burst() -> Vec<u8> {
+        slots([Burst::fp(AField::nt([1; 5])), Burst::pp(AField::nt([2; 5]))]).into_vec()
+    }
+
+    /// The channel of the next A-field.
+    async fn next_a_field(rx: &mut Receiver<anyhow::Result<DecodedPacket>>) -> usize {
+        loop {
+            let packet = time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            if matches!(packet.packet, Packet::A { .. }) {
+                return packet.channel;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_demux() {
+        let demux = Demux::bind(0).await.unwrap();
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, demux.port));
+        let (tx, mut rx) = queue::bounded(16, Overflow::Block);
+        tokio::spawn(demux.run(Arc::default(), new_channel, tx));
+
+        let burst = burst();
+        for _ in 0..2 {
+            let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            sender.send_to(&burst, addr).await.unwrap();
+        }
+
+        let mut channels = BTreeSet::new();
+        while channels.len() < 2 {
+            channels.insert(next_a_field(&mut rx).await);
+        }
+        assert_eq!(channels, BTreeSet::from([0, 1]));
+    }
+
+    #[tokio::test]
+    async fn test_max_senders() {
+        let demux = Demux::bind(0)
+            .await
+            .unwrap()
+            .with_max_senders(1)
+            .with_idle_timeout(Duration::from_millis(200));
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, demux.port));
+        let (tx, mut rx) = queue::bounded(16, Overflow::Block);
+        tokio::spawn(demux.run(Arc::default(), new_channel, tx));
+
+        let burst = burst();
+        let first = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let second = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        first.send_to(&burst, addr).await.unwrap();
+        assert_eq!(next_a_field(&mut rx).await, 0);
+
+        // Ignored while the first sender is active, accepted once it has gone quiet.
+        second.send_to(&burst, addr).await.unwrap();
+        time::sleep(Duration::from_millis(100)).await;
+        first.send_to(&burst, addr).await.unwrap();
+        time::sleep(Duration::from_millis(500)).await;
+        second.send_to(&burst, addr).await.unwrap();
+
+        // Had its first datagram been accepted, the second sender would have had two channels by
+        // now, the first stopped after it went quiet.
+        let mut channels = BTreeSet::new();
+        while let Ok(Some(packet)) = time::timeout(Duration::from_millis(300), rx.recv()).await {
+            let packet = packet.unwrap();
+            if matches!(packet.packet, Packet::A { .. }) {
+                channels.insert(packet.channel);
+            }
+        }
+        assert_eq!(channels, BTreeSet::from([0, 1]));
+    }
+
+    #[tokio::test]
+    async fn test_dropped() {
+        let demux = Demux::bind(0).await.unwrap();
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, demux.port));
+        // Nothing is received at first, so the channel blocks and its datagrams pile up.
+        let (tx, mut rx) = queue::bounded(1, Overflow::Block);
+        tokio::spawn(demux.run(Arc::default(), new_channel, tx));
+
+        let burst = burst();
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        for _ in 0..super::SENDER_QUEUE * 2 {
+            sender.send_to(&burst, addr).await.unwrap();
+        }
+        time::sleep(Duration::from_millis(100)).await;
+        sender.send_to(&burst, addr).await.unwrap();
+
+        loop {
+            let packet = time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            if let Packet::Gap {
+                bits,
+                dropped: true,
+            } = packet.packet
+            {
+                assert!(bits >= burst.len() as u64 * 8);
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! Building bursts: the inverse of the [`decoder`](crate::decoder).
+//!
+//! An [`AField`] is put together from its header fields and a tail and gets its R-CRC appended;
+//! a [`Burst`] adds the S-field and B-field. [`slots`] lays bursts out on the slot grid as one
+//! bit stream, ready to be decoded again or handed to a modulator.
+
+use bitvec::{order::Msb0, vec::BitVec};
+
+use crate::{
+    crc,
+    decoder::{b_field_type, BA_NO_B_FIELD, B_FIELD_BITS, FP_SYNC, PP_SYNC, SLOT_BITS},
+    scramble::scramble,
+};
+
+pub use crate::decoder::TailType;
+
+/// An A-field under construction: header octet and 40-bit tail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AField {
+    header: u8,
+    tail: [u8; 5],
+}
+
+impl AField {
+    /// An A-field with the given tail type, no B-field and an all-zero tail.
+    pub fn new(ta: TailType) -> Self {
+        Self {
+            header: (ta as u8) << 5 | BA_NO_B_FIELD << 1,
+            tail: [0; 5],
+        }
+    }
+
+    /// An identities tail carrying a fixed part's RFPI.
+    pub fn nt(rfpi: [u8; 5]) -> Self {
+        Self::new(TailType::Nt).with_tail(rfpi)
+    }
+
+    /// A system information tail: 4-bit header and 36 bits of data.
+    pub fn qt(header: u8, data: u64) -> Self {
+        let tail = (u64::from(header & 0xf) << 36) | (data & 0xf_ffff_ffff);
+        Self::new(TailType::Qt).with_tail(tail_bytes(tail))
+    }
+
+    /// A MAC control tail: 8-bit command and 32 bits of parameters.
+    pub fn mt(command: u8, params: u32) -> Self {
+        let tail = (u64::from(command) << 32) | u64::from(params);
+        Self::new(TailType::Mt).with_tail(tail_bytes(tail))
+    }
+
+    /// Sets the Q1 bit.
+    pub fn with_q1(mut self, q1: bool) -> Self {
+        self.header = (self.header & !0x10) | u8::from(q1) << 4;
+        self
+    }
+
+    /// Sets the BA bits, which tell what the B-field holds ([`BA_NO_B_FIELD`] for none).
+    pub fn with_ba(mut self, ba: u8) -> Self {
+        self.header = (self.header & !0x0e) | (ba & 7) << 1;
+        self
+    }
+
+    /// Sets the Q2 bit.
+    pub fn with_q2(mut self, q2: bool) -> Self {
+        self.header = (self.header & !0x01) | u8::from(q2);
+        self
+    }
+
+    /// Sets the 40 tail bits.
+    pub fn with_tail(mut self, tail: [u8; 5]) -> Self {
+        self.tail = tail;
+        self
+    }
+
+    /// The header octet.
+    pub fn header(&self) -> u8 {
+        self.header
+    }
+
+    /// The 64 bits on air: header, tail and R-CRC.
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut bytes = [0u8; 8];
+        bytes[0] = self.header;
+        bytes[1..6].copy_from_slice(&self.tail);
+
+        let crc = crc::generate(&bytes[..6]);
+        bytes[6..].copy_from_slice(&crc.to_be_bytes());
+        bytes
+    }
+}
+
+fn tail_bytes(tail: u64) -> [u8; 5] {
+    let bytes = tail.to_be_bytes();
+    [bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]
+}
+
+/// A complete burst: S-field, A-field and, unless the header says otherwise, a B-field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Burst {
+    /// Preamble and sync word.
+    s_field: u32,
+    a_field: AField,
+    b_field: Option<BitVec<u8, Msb0>>,
+    /// Frame number the B-field is scrambled for, if it is.
+    frame: Option<u8>,
+}
+
+impl Burst {
+    /// A burst sent by a fixed part.
+    pub fn fp(a_field: AField) -> Self {
+        Self {
+            s_field: 0xaa << 24 | FP_SYNC,
+            a_field,
+            b_field: None,
+            frame: None,
+        }
+    }
+
+    /// A burst sent by a portable part.
+    pub fn pp(a_field: AField) -> Self {
+        Self {
+            s_field: 0x55 << 24 | PP_SYNC,
+            a_field,
+            b_field: None,
+            frame: None,
+        }
+    }
+
+    /// Attaches a B-field, zero-padded or cut to [`B_FIELD_BITS`].
+    ///
+    /// If the A-field header still says [`BA_NO_B_FIELD`], its BA bits are cleared.
+    pub fn with_b_field(mut self, mut b_field: BitVec<u8, Msb0>) -> Self {
+        b_field.resize(B_FIELD_BITS, false);
+        self.b_field = Some(b_field);
+        if b_field_type(self.a_field.header) == BA_NO_B_FIELD {
+            self.a_field = self.a_field.with_ba(0);
+        }
+        self
+    }
+
+    /// Scrambles the B-field on air for a burst in frame `frame`, as DECT equipment does.
+    pub fn with_scrambling(mut self, frame: u8) -> Self {
+        self.frame = Some(frame);
+        self
+    }
+
+    /// The A-field, with BA as it will be sent.
+    pub fn a_field(&self) -> &AField {
+        &self.a_field
+    }
+
+    /// Appends the burst to `out`. A missing B-field is sent as zeros if the header announces
+    /// one.
+    pub fn write(&self, out: &mut BitVec<u8, Msb0>) {
+        push_bits(out, u64::from(self.s_field), 32);
+        push_bits(out, u64::from_be_bytes(self.a_field.to_bytes()), 64);
+
+        if b_field_type(self.a_field.header) != BA_NO_B_FIELD {
+            let start = out.len();
+            match &self.b_field {
+                Some(b_field) => out.extend_from_bitslice(b_field),
+                None => out.resize(out.len() + B_FIELD_BITS, false),
+            }
+            if let Some(frame) = self.frame {
+                scramble(frame, &mut out[start..]);
+            }
+        }
+    }
+
+    /// The burst on its own.
+    pub fn to_bits(&self) -> BitVec<u8, Msb0> {
+        let mut out = BitVec::new();
+        self.write(&mut out);
+        out
+    }
+}
+
+fn push_bits(out: &mut BitVec<u8, Msb0>, value: u64, n: usize) {
+    for i in (0..n).rev() {
+        out.push(value >> i & 1 != 0);
+    }
+}
+
+/// Places each burst at the start of its own slot, filling the rest of the slot with zeros.
+pub fn slots(bursts: impl IntoIterator<Item = Burst>) -> BitVec<u8, Msb0> {
+    let mut out = BitVec::new();
+    for burst in bursts {
+        let start = out.len();
+        burst.write(&mut out);
+        out.resize(start + SLOT_BITS as usize, false);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use bitvec::{bitvec, order::Msb0, vec::BitVec};
+
+    use super::{slots, AField, Burst, TailType};
+    use crate::{
+        crc::Rcrc,
+        decoder::{b_field_type, decode_all, Packet, BA_NO_B_FIELD, B_FIELD_BITS},
+    };
+
+    #[test]
+    fn test_a_field() {
+        // The A-field of the real capture in the decoder tests.
+        let a_field = AField::nt([0x10, 0x2a, 0xf1, 0x2c, 0x0d])
+            .with_ba(0)
+            .with_q2(true);
+        assert_eq!(a_field.to_bytes(), 0x6110_2af1_2c0d_3cd5u64.to_be_bytes());
+
+        let a_field = AField::new(TailType::Pt).with_q1(true).with_tail([0xff; 5]);
+        assert_eq!(a_field.header(), 0b1111_1110);
+        assert_eq!(a_field.to_bytes().crc(), 0);
+
+        assert_eq!(
+            AField::qt(0x3, 0x1_2345_6789).to_bytes()[1..6],
+            [0x31, 0x23, 0x45, 0x67, 0x89]
+        );
+        assert_eq!(
+            AField::mt(0xab, 0x1234_5678).to_bytes()[1..6],
+            [0xab, 0x12, 0x34, 0x56, 0x78]
+        );
+    }
+
+    #[test]
+    fn test_burst_round_trip() {
+        let b_field = bitvec![u8, Msb0; 1, 0, 1, 1];
+        let bursts = [
+            Burst::fp(AField::nt([1, 2, 3, 4, 5])),
+            Burst::pp(AField::mt(0x42, 7)).with_b_field(b_field.clone()),
+        ];
+        assert_eq!(slots(bursts.clone()).len(), 2 * 480);
+
+        // The decoder only sees a sync with 32 bits of input ahead of its S-field.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        bits.extend_from_bitslice(&slots(bursts.clone()));
+
+        let packets = decode_all(bits.as_raw_slice()).unwrap();
+        let [Packet::Header { .. }, Packet::A {
+            header,
+            tail,
+            b: None,
+            ..
+        }, Packet::Header { .. }, Packet::A {
+            b: Some(b),
+            corrected: false,
+            ..
+        }] = &packets[..]
+        else {
+            panic!("unexpected packets: {packets:?}");
+        };
+
+        assert_eq!(*header, bursts[0].a_field.header());
+        assert_eq!(b_field_type(*header), BA_NO_B_FIELD);
+        assert_eq!(tail, &[1, 2, 3, 4, 5]);
+        assert_eq!(b.len(), B_FIELD_BITS);
+        assert_eq!(b[..4], b_field);
+        assert!(b[4..].not_any());
+    }
+
+    #[test]
+    fn test_round_trip_every_header() {
+        let tail_types = [
+            TailType::Ct0,
+            TailType::Ct1,
+            TailType::NtConnectionless,
+            TailType::Nt,
+            TailType::Qt,
+            TailType::Escape,
+            TailType::Mt,
+            TailType::Pt,
+        ];
+
+        let mut bursts = Vec::new();
+        for (i, ta) in tail_types.into_iter().enumerate() {
+            for ba in 0..8u8 {
+                for (q1, q2) in [(false, false), (true, false), (false, true), (true, true)] {
+                    let n = bursts.len() as u8;
+                    let a_field = AField::new(ta)
+                        .with_q1(q1)
+                        .with_ba(ba)
+                        .with_q2(q2)
+                        .with_tail([n, !n, i as u8, ba, 0x5a]);
+                    let burst = if n.is_multiple_of(2) {
+                        Burst::fp(a_field)
+                    } else {
+                        Burst::pp(a_field)
+                    };
+                    let burst = if ba == BA_NO_B_FIELD {
+                        burst
+                    } else {
+                        let b_field: BitVec<u8, Msb0> = (0..B_FIELD_BITS)
+                            .map(|bit| (bit * 7 + n as usize).is_multiple_of(3))
+                            .collect();
+                        burst.with_b_field(b_field)
+                    };
+                    bursts.push(burst);
+                }
+            }
+        }
+
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        bits.extend_from_bitslice(&slots(bursts.clone()));
+        let packets = decode_all(bits.as_raw_slice()).unwrap();
+        assert_eq!(packets.len(), 2 * bursts.len());
+
+        for (burst, packet) in bursts.iter().zip(packets.chunks(2)) {
+            let [Packet::Header { sync, .. }, Packet::A {
+                header,
+                tail,
+                crc,
+                b,
+                corrected: false,
+            }] = packet
+            else {
+                panic!("unexpected packets: {packet:?}");
+            };
+
+            let sent = burst.a_field().to_bytes();
+            assert_eq!(u16::from_be(*sync), burst.s_field as u16);
+            assert_eq!(*header, sent[0]);
+            assert_eq!(tail[..], sent[1..6]);
+            assert_eq!(crc.to_be_bytes(), sent[6..]);
+            assert_eq!(b.as_ref(), burst.b_field.as_ref());
+        }
+    }
+
+    #[test]
+    fn test_scrambled_round_trip() {
+        let b_field = BitVec::<u8, Msb0>::repeat(false, B_FIELD_BITS);
+        let burst = Burst::fp(AField::nt([1, 2, 3, 4, 5]))
+            .with_b_field(b_field.clone())
+            .with_scrambling(6);
+
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        bits.extend_from_bitslice(&slots([burst]));
+        let mut packets = decode_all(bits.as_raw_slice()).unwrap();
+
+        let Packet::A { b: Some(b), .. } = &packets[1] else {
+            panic!("unexpected packets: {packets:?}");
+        };
+        assert_ne!(*b, b_field);
+
+        packets[1].descramble(6);
+        let Packet::A { b: Some(b), .. } = &packets[1] else {
+            unreachable!();
+        };
+        assert_eq!(*b, b_field);
+    }
+}
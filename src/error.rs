@@ -0,0 +1,22 @@
+//! Errors returned by the decoder.
+
+use thiserror::Error;
+
+/// Result type of the decoding API.
+pub type Result<T, E = DecodeError> = core::result::Result<T, E>;
+
+/// Why decoding failed.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The A-field failed its R-CRC and could not be repaired.
+    #[error("A-field R-CRC mismatch (syndrome {syndrome:#06x})")]
+    Crc {
+        /// Non-zero R-CRC syndrome of the field as received.
+        syndrome: u16,
+    },
+    /// Reading the input failed.
+    #[cfg(feature = "std")]
+    #[error("reading input failed")]
+    Source(#[from] std::io::Error),
+}
@@ -0,0 +1,269 @@
+//! C API; see `include/dectdump.h`.
+//!
+//! Bytes go in with [`dect_decoder_push`], packets come out one at a time with
+//! [`dect_decoder_pull`].
+
+use alloc::{boxed::Box, collections::VecDeque};
+use core::{ptr, slice};
+
+use crate::decoder::{pack_bits, BitIterator, Decoder, Packet};
+
+/// [`DectPacket::kind`] of a sync.
+pub const DECT_PACKET_HEADER: u8 = 0;
+/// [`DectPacket::kind`] of an A-field.
+pub const DECT_PACKET_A: u8 = 1;
+/// [`DectPacket::kind`] of a gap in the decoded input.
+pub const DECT_PACKET_GAP: u8 = 2;
+/// Capacity of [`DectPacket::b`] in bytes.
+pub const DECT_B_FIELD_MAX: usize = 64;
+
+/// A decoder plus the packets it produced that were not pulled yet.
+#[derive(Debug)]
+pub struct DectDecoder {
+    decoder: Decoder,
+    pending: VecDeque<Packet>,
+}
+
+/// A decoded packet, flattened for C.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DectPacket {
+    /// [`DECT_PACKET_HEADER`], [`DECT_PACKET_A`] or [`DECT_PACKET_GAP`].
+    pub kind: u8,
+    /// Low 16 bits of the sync word, e.g. `0xe98a` from a fixed part (headers only).
+    pub sync: u16,
+    /// A-field header octet.
+    pub header: u8,
+    /// The 40-bit A-field tail.
+    pub tail: [u8; 5],
+    /// The received R-CRC.
+    pub crc: u16,
+    /// Whether a bit error in the A-field was corrected.
+    pub corrected: bool,
+    /// Number of valid bits in `b`; zero if there is no B-field.
+    pub b_bits: usize,
+    /// The B-field, MSB first.
+    pub b: [u8; DECT_B_FIELD_MAX],
+    /// Length of a gap in bits (gaps only).
+    pub gap_bits: u64,
+    /// Whether the gap's input was discarded unread (gaps only).
+    pub gap_dropped: bool,
+}
+
+impl From<Packet> for DectPacket {
+    fn from(packet: Packet) -> Self {
+        let mut out = DectPacket {
+            kind: DECT_PACKET_HEADER,
+            sync: 0,
+            header: 0,
+            tail: [0; 5],
+            crc: 0,
+            corrected: false,
+            b_bits: 0,
+            b: [0; DECT_B_FIELD_MAX],
+            gap_bits: 0,
+            gap_dropped: false,
+        };
+
+        match packet {
+            Packet::Header { sync, .. } => out.sync = u16::from_be(sync),
+            Packet::A {
+                header,
+                tail,
+                crc,
+                b,
+                corrected,
+            } => {
+                out.kind = DECT_PACKET_A;
+                out.header = header;
+                out.tail = tail;
+                out.crc = crc;
+                out.corrected = corrected;
+                if let Some(b) = b {
+                    let bytes = pack_bits(&b);
+                    let len = bytes.len().min(DECT_B_FIELD_MAX);
+                    out.b[..len].copy_from_slice(&bytes[..len]);
+                    out.b_bits = b.len().min(DECT_B_FIELD_MAX * 8);
+                }
+            }
+            Packet::Gap { bits, dropped } => {
+                out.kind = DECT_PACKET_GAP;
+                out.gap_bits = bits;
+                out.gap_dropped = dropped;
+            }
+        }
+        out
+    }
+}
+
+/// Creates a decoder. Free it with [`dect_decoder_free`].
+#[no_mangle]
+pub extern "C" fn dect_decoder_new(correct_errors: bool) -> *mut DectDecoder {
+    let decoder = Decoder::new(BitIterator::new([])).with_error_correction(correct_errors);
+    Box::into_raw(Box::new(DectDecoder {
+        decoder,
+        pending: VecDeque::new(),
+    }))
+}
+
+/// Frees a decoder. Null is ignored.
+///
+/// # Safety
+///
+/// `decoder` must be null or come from [`dect_decoder_new`], and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn dect_decoder_free(decoder: *mut DectDecoder) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+/// Feeds `len` bytes to the decoder. Returns the number of packets waiting to be pulled, or -1
+/// on error.
+///
+/// Any length is accepted; input larger than the decoder's buffer is decoded a piece at a time.
+/// Waiting packets are kept until pulled, so pull them all after every push.
+///
+/// # Safety
+///
+/// `decoder` must come from [`dect_decoder_new`], and `data` must point to `len` readable bytes
+/// (or be null if `len` is zero).
+#[no_mangle]
+pub unsafe extern "C" fn dect_decoder_push(
+    decoder: *mut DectDecoder,
+    data: *const u8,
+    len: usize,
+) -> isize {
+    let Some(decoder) = decoder.as_mut() else {
+        return -1;
+    };
+    let data = match len {
+        0 => &[],
+        _ if data.is_null() => return -1,
+        _ => slice::from_raw_parts(data, len),
+    };
+
+    match decoder.decoder.push(data) {
+        Ok(packets) => {
+            decoder.pending.extend(packets);
+            decoder.pending.len() as isize
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Moves the oldest waiting packet into `out`. Returns false if there is none.
+///
+/// # Safety
+///
+/// `decoder` must come from [`dect_decoder_new`], and `out` must point to writable memory for a
+/// [`DectPacket`].
+#[no_mangle]
+pub unsafe extern "C" fn dect_decoder_pull(
+    decoder: *mut DectDecoder,
+    out: *mut DectPacket,
+) -> bool {
+    let Some(decoder) = decoder.as_mut() else {
+        return false;
+    };
+    if out.is_null() {
+        return false;
+    }
+
+    match decoder.pending.pop_front() {
+        Some(packet) => {
+            ptr::write(out, packet.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+    use core::{mem::MaybeUninit, ptr};
+
+    use bitvec::{bitvec, order::Msb0, vec::BitVec};
+
+    use super::*;
+    use crate::{
+        decoder::{DEFAULT_BUFFER_LIMIT, FP_SYNC},
+        encode::{AField, Burst},
+        synth::single_burst,
+    };
+
+    #[test]
+    fn test_push_pull() {
+        unsafe {
+            let decoder = dect_decoder_new(false);
+            assert_eq!(dect_decoder_push(decoder, ptr::null(), 0), 0);
+            assert_eq!(dect_decoder_push(decoder, ptr::null(), 1), -1);
+
+            let (data, _) = single_burst();
+            let n = dect_decoder_push(decoder, data.as_ptr(), data.len());
+            assert_eq!(n, 2);
+
+            let mut packet = MaybeUninit::uninit();
+            assert!(dect_decoder_pull(decoder, packet.as_mut_ptr()));
+            assert_eq!(packet.assume_init().kind, DECT_PACKET_HEADER);
+            assert!(dect_decoder_pull(decoder, packet.as_mut_ptr()));
+            let packet = packet.assume_init();
+            assert_eq!(packet.kind, DECT_PACKET_A);
+            assert!(packet.b_bits > 0);
+
+            let mut packet = MaybeUninit::uninit();
+            assert!(!dect_decoder_pull(decoder, packet.as_mut_ptr()));
+
+            dect_decoder_free(decoder);
+            dect_decoder_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_push_large() {
+        let (burst, _) = single_burst();
+        let bursts = DEFAULT_BUFFER_LIMIT / burst.len() + 10;
+        let data = burst.repeat(bursts);
+
+        unsafe {
+            let decoder = dect_decoder_new(false);
+            assert!(dect_decoder_push(decoder, data.as_ptr(), data.len()) > 0);
+
+            let mut a_fields = 0;
+            let mut packet = MaybeUninit::uninit();
+            while dect_decoder_pull(decoder, packet.as_mut_ptr()) {
+                let packet = packet.assume_init_ref();
+                assert!(packet.kind != DECT_PACKET_GAP || !packet.gap_dropped);
+                if packet.kind == DECT_PACKET_A {
+                    a_fields += 1;
+                }
+            }
+            assert_eq!(a_fields, bursts);
+            dect_decoder_free(decoder);
+        }
+    }
+
+    #[test]
+    fn test_unaligned_burst() {
+        // The burst starts 3 bits into a byte, so its B-field does too.
+        let mut bits = bitvec![u8, Msb0; 0; 35];
+        Burst::fp(AField::nt([1; 5]))
+            .with_b_field(BitVec::from_vec(vec![0xc3; 40]))
+            .write(&mut bits);
+        bits.resize(bits.len() + 64, false);
+        let data = bits.into_vec();
+
+        let mut decoder = Decoder::new(BitIterator::new([]));
+        let packets: Vec<DectPacket> = decoder
+            .push(&data)
+            .unwrap()
+            .into_iter()
+            .map(DectPacket::from)
+            .collect();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(u32::from(packets[0].sync), FP_SYNC & 0xffff);
+        assert_eq!(packets[1].b_bits, 320);
+        assert_eq!(packets[1].b[..40], [0xc3; 40]);
+    }
+}
@@ -0,0 +1,93 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use clap::Args;
+use dectdump::{
+    modulate::{write_cf32, Modulator},
+    synth::Synth,
+};
+
+#[derive(Debug, Clone, Args)]
+pub struct GenerateArgs {
+    /// Seed for the random traffic; the same seed and options give the same capture.
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+
+    /// Number of slots to generate, 24 per frame.
+    #[arg(long, default_value_t = 24 * 100)]
+    slots: usize,
+
+    /// Fraction of slots that carry a burst.
+    #[arg(long, value_name = "RATIO", default_value_t = 0.5)]
+    occupancy: f64,
+
+    /// Fraction of bursts sent by a fixed part; the rest come from portable parts.
+    #[arg(long, value_name = "RATIO", default_value_t = 0.5)]
+    fp_ratio: f64,
+
+    /// Fraction of bursts that carry a B-field.
+    #[arg(long, value_name = "RATIO", default_value_t = 0.5)]
+    b_field_ratio: f64,
+
+    /// Leave the gaps between bursts as zeros instead of random bits.
+    #[arg(long)]
+    no_noise: bool,
+
+    /// Probability of flipping each bit.
+    #[arg(long, value_name = "RATE", default_value_t = 0.0)]
+    bit_error_rate: f64,
+
+    /// Fraction of bursts with a damaged R-CRC.
+    #[arg(long, value_name = "RATE", default_value_t = 0.0)]
+    crc_error_rate: f64,
+
+    /// Write GFSK-modulated cf32 IQ samples at this sample rate instead of bits.
+    #[arg(long, value_name = "HZ")]
+    iq_rate: Option<f64>,
+
+    /// Output file; standard output if not given.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+impl GenerateArgs {
+    /// Writes the capture and reports what went into it on stderr.
+    pub fn run(&self) -> Result<()> {
+        let capture = Synth::new(self.seed)
+            .with_slots(self.slots)
+            .with_occupancy(self.occupancy)
+            .with_fp_ratio(self.fp_ratio)
+            .with_b_field_ratio(self.b_field_ratio)
+            .with_noise(!self.no_noise)
+            .with_bit_error_rate(self.bit_error_rate)
+            .with_crc_error_rate(self.crc_error_rate)
+            .generate();
+
+        let out: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(File::create(path)?),
+            None => Box::new(io::stdout().lock()),
+        };
+        let mut out = BufWriter::new(out);
+
+        let corrupted = capture.bursts.iter().filter(|b| b.crc_corrupted).count();
+        eprintln!(
+            "{} bursts ({corrupted} with a damaged R-CRC), {} bit errors",
+            capture.bursts.len(),
+            capture.bit_errors
+        );
+
+        match self.iq_rate {
+            Some(rate) => {
+                let samples = Modulator::new(rate).modulate(&capture.bits);
+                write_cf32(&samples, &mut out)?;
+            }
+            None => out.write_all(&capture.into_bytes())?,
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
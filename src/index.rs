@@ -0,0 +1,270 @@
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use dectdump::{
+    clock::CaptureClock,
+    decoder::Decoder,
+    modulate::BIT_RATE,
+    source::FileSource,
+    stream::{DataSource, DecoderStream},
+};
+use tokio_stream::StreamExt;
+
+/// Length of a multiframe (16 frames of 10 ms).
+const MULTIFRAME: Duration = Duration::from_millis(160);
+
+#[derive(Debug, Clone, Args)]
+pub struct IndexArgs {
+    /// Capture file holding a demodulated bit stream. The index is written next to it, with
+    /// `.idx` appended to the name.
+    input: PathBuf,
+}
+
+impl IndexArgs {
+    pub async fn run(&self, decoder: Decoder) -> Result<()> {
+        let source = FileSource::open(&self.input)
+            .await
+            .with_context(|| format!("{}", self.input.display()))?;
+        let entries = build(source, decoder).await?;
+
+        let path = sidecar(&self.input);
+        let out = File::create(&path).with_context(|| format!("{}", path.display()))?;
+        write(&entries, BufWriter::new(out))?;
+        eprintln!(
+            "{} multiframes indexed in {}",
+            entries.len(),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+/// The first burst of a multiframe in a capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// Byte offset of the burst in the capture, rounded down.
+    pub offset: u64,
+    /// Multiframe number broadcast by the fixed part.
+    pub multiframe: u32,
+    /// Time of the burst since the start of the capture at the nominal bit rate, as `--from`
+    /// counts it without an index.
+    pub time: Duration,
+}
+
+/// Where the index of the capture at `path` lives.
+pub fn sidecar(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".idx");
+    name.into()
+}
+
+/// Decodes a whole capture and notes where every multiframe starts. Multiframes before the fixed
+/// part first broadcasts its multiframe number are not indexed.
+///
+/// Entries are in capture order, so their times only increase. Fixed parts sharing the capture
+/// each count their own multiframes; entries closer than a quarter of a multiframe to the last one
+/// are left out, so that they do not take turns adding one every frame.
+pub async fn build(source: impl DataSource, decoder: Decoder) -> Result<Vec<Entry>> {
+    let mut packets = DecoderStream::new(source, decoder);
+    let mut entries: Vec<Entry> = Vec::new();
+    while let Some(packet) = packets.next().await {
+        let packet = packet?;
+        let Some(multiframe) = packet.frame.and_then(|frame| frame.multiframe) else {
+            continue;
+        };
+        // Whole milliseconds, as written to the index.
+        let time = CaptureClock::duration(packet.position).as_millis();
+        let time = Duration::from_millis(time as u64);
+        if entries
+            .last()
+            .is_some_and(|e| e.multiframe == multiframe || time < e.time + MULTIFRAME / 4)
+        {
+            continue;
+        }
+        entries.push(Entry {
+            // The decoder finds a sync word at the end of a 64-bit window, so it needs the 32
+            // bits before the S-field too.
+            offset: packet.position.saturating_sub(32) / 8,
+            multiframe,
+            time,
+        });
+    }
+    Ok(entries)
+}
+
+/// Writes `offset,multiframe,time` lines with a header line, the time in seconds.
+pub fn write(entries: &[Entry], mut out: impl Write) -> Result<()> {
+    writeln!(out, "offset,multiframe,time")?;
+    for entry in entries {
+        writeln!(
+            out,
+            "{},{},{:.3}",
+            entry.offset,
+            entry.multiframe,
+            entry.time.as_secs_f64()
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Reads what [`write`] wrote.
+pub fn read(input: impl BufRead) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for (number, line) in input.lines().enumerate().skip(1) {
+        let line = line?;
+        let fields: Vec<&str> = line.split(',').collect();
+        let [offset, multiframe, time] = fields[..] else {
+            bail!("line {}: expected 3 fields", number + 1);
+        };
+        let entry = (|| {
+            Ok::<_, anyhow::Error>(Entry {
+                offset: offset.parse()?,
+                multiframe: multiframe.parse()?,
+                time: Duration::try_from_secs_f64(time.parse()?)?,
+            })
+        })()
+        .with_context(|| format!("line {}", number + 1))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// The byte offset to start reading at to decode from `from` into the capture.
+///
+/// Uses the last indexed multiframe that starts no later than `from`. Without a usable index,
+/// the offset is worked out from the bit rate, which is only right if no input was dropped.
+pub fn offset(path: &Path, from: Duration) -> Result<u64> {
+    let index = sidecar(path);
+    let entries = if index.exists() {
+        let file = File::open(&index).with_context(|| format!("{}", index.display()))?;
+        read(BufReader::new(file)).with_context(|| format!("{}", index.display()))?
+    } else {
+        Vec::new()
+    };
+    if entries.is_empty() {
+        eprintln!(
+            "no multiframes indexed in {}, assuming a continuous bit stream",
+            index.display()
+        );
+        return Ok((from.as_secs_f64() * BIT_RATE / 8.0) as u64);
+    }
+    Ok(find(&entries, from))
+}
+
+fn find(entries: &[Entry], from: Duration) -> u64 {
+    let after = entries.partition_point(|e| e.time <= from);
+    after.checked_sub(1).map_or(0, |i| entries[i].offset)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use bitvec::{bitvec, order::Msb0};
+    use dectdump::{
+        decoder::{BitIterator, Decoder, Packet},
+        encode::{AField, Burst},
+    };
+
+    use super::{build, find, read, write};
+
+    #[tokio::test]
+    async fn test_index() {
+        // A multiframe number broadcast in frame 8, then FP bursts in the same slot.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        let mut burst = |frame: usize, burst: Burst| {
+            bits.resize(480 + frame * 24 * 480, false);
+            burst.write(&mut bits);
+        };
+        burst(8, Burst::fp(AField::qt(0b0110, 41)));
+        for frame in [9, 15, 16, 20, 32, 40] {
+            burst(frame, Burst::fp(AField::nt([1; 5])));
+        }
+        bits.resize(bits.len() + 480, false);
+
+        let bytes = bits.into_vec();
+        let source = tokio_stream::iter([Ok(bytes.clone())]);
+        let entries = build(source, Decoder::new(BitIterator::new([])))
+            .await
+            .unwrap();
+        let frame_bytes = 24 * 480 / 8;
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.multiframe, e.time.as_millis()))
+                .collect::<Vec<_>>(),
+            [(41, 80), (42, 160), (43, 320)]
+        );
+        for (entry, frame) in entries.iter().zip([8, 16, 32]) {
+            assert_eq!(
+                entry.offset,
+                (480 + frame * frame_bytes * 8 - 32) as u64 / 8
+            );
+
+            // Decoding from an entry starts with the burst it names.
+            let offset = entry.offset as usize;
+            let mut decoder = Decoder::new(BitIterator::new([]));
+            let packets = decoder.push(&bytes[offset..offset + 80]).unwrap();
+            assert!(matches!(
+                packets[..],
+                [Packet::Header { .. }, Packet::A { .. }]
+            ));
+            let position = offset as u64 * 8 + decoder.burst_position();
+            assert_eq!(position, (480 + frame * frame_bytes * 8) as u64);
+        }
+
+        let mut out = Vec::new();
+        write(&entries, &mut out).unwrap();
+        assert_eq!(read(&out[..]).unwrap(), entries);
+
+        assert_eq!(find(&entries, Duration::ZERO), 0);
+        assert_eq!(
+            find(&entries, Duration::from_millis(100)),
+            entries[0].offset
+        );
+        assert_eq!(
+            find(&entries, Duration::from_millis(200)),
+            entries[1].offset
+        );
+        assert_eq!(find(&entries, Duration::from_secs(60)), entries[2].offset);
+        assert_eq!(find(&[], Duration::from_secs(60)), 0);
+    }
+
+    #[tokio::test]
+    async fn test_index_two_fixed_parts() {
+        // Two fixed parts in slots 0 and 6, far apart in their multiframe counts.
+        let mut bits = bitvec![u8, Msb0; 0; 480];
+        for frame in 0..64 {
+            for (slot, multiframe) in [(0, 41), (6, 900)] {
+                bits.resize(480 + (frame * 24 + slot) * 480, false);
+                let a_field = match frame % 16 {
+                    8 => AField::qt(0b0110, multiframe + frame as u64 / 16),
+                    _ => AField::nt([slot as u8; 5]),
+                };
+                Burst::fp(a_field).write(&mut bits);
+            }
+        }
+        bits.resize(bits.len() + 480, false);
+
+        let source = tokio_stream::iter([Ok(bits.into_vec())]);
+        let entries = build(source, Decoder::new(BitIterator::new([])))
+            .await
+            .unwrap();
+        // 64 frames take 640 ms, so at most one entry every 40 ms.
+        assert!(!entries.is_empty() && entries.len() <= 16);
+        assert!(entries
+            .windows(2)
+            .all(|e| e[0].offset < e[1].offset && e[0].time < e[1].time));
+        let from = Duration::from_millis(400);
+        let found = find(&entries, from);
+        assert!(entries.iter().any(|e| e.offset == found && e.time <= from));
+    }
+}
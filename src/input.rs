@@ -0,0 +1,179 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use dectdump::{clock::CaptureClock, source::FileSource};
+
+/// How much of a file [`sniff`] looks at.
+const HEAD_LEN: usize = 4096;
+
+/// What `--input` holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    /// Look at the file and refuse anything that is not a demodulated bit stream.
+    Auto,
+    /// A demodulated bit stream, one bit per bit, MSB first, as sent over UDP.
+    Bits,
+}
+
+/// What a file looks like it holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detected {
+    /// A demodulated bit stream.
+    Bits,
+    /// A pcap or pcapng packet capture.
+    Pcap,
+    /// A SigMF recording.
+    SigMf,
+    /// Complex float samples (cf32), e.g. from an SDR or `dectdump generate --iq-rate`.
+    Iq,
+}
+
+/// Guesses what the file at `path` holds from its name and its first bytes, `head`.
+///
+/// A bit stream has no header, so it is whatever is left once the other formats are ruled out.
+pub fn sniff(path: &Path, head: &[u8]) -> Detected {
+    const PCAP_MAGICS: [[u8; 4]; 5] = [
+        [0xd4, 0xc3, 0xb2, 0xa1],
+        [0xa1, 0xb2, 0xc3, 0xd4],
+        [0x4d, 0x3c, 0xb2, 0xa1],
+        [0xa1, 0xb2, 0x3c, 0x4d],
+        // pcapng section header block
+        [0x0a, 0x0d, 0x0d, 0x0a],
+    ];
+    if head.len() >= 4 && PCAP_MAGICS.iter().any(|magic| head[..4] == *magic) {
+        return Detected::Pcap;
+    }
+
+    let extension = path.extension().and_then(|e| e.to_str());
+    if matches!(extension, Some("sigmf-meta" | "sigmf-data" | "sigmf")) {
+        return Detected::SigMf;
+    }
+
+    if looks_like_cf32(head) {
+        return Detected::Iq;
+    }
+    Detected::Bits
+}
+
+/// Baseband samples are small floats, while a bit stream read as floats is all over the range.
+fn looks_like_cf32(head: &[u8]) -> bool {
+    let values: Vec<f32> = head
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    if values.len() < 64 {
+        return false;
+    }
+    let small = values
+        .iter()
+        .filter(|v| v.is_finite() && v.abs() <= 4.0)
+        .count();
+    // Zero runs are small too, but say nothing about the format.
+    let nonzero = values.iter().filter(|v| **v != 0.0).count();
+    small * 20 >= values.len() * 19 && nonzero * 2 >= values.len()
+}
+
+/// Opens `--input`, checking first that it holds something the decoder can use.
+pub async fn open(path: &Path, format: InputFormat) -> Result<FileSource> {
+    if format == InputFormat::Auto {
+        let mut head = Vec::with_capacity(HEAD_LEN);
+        File::open(path)
+            .and_then(|file| file.take(HEAD_LEN as u64).read_to_end(&mut head))
+            .with_context(|| format!("{}", path.display()))?;
+        match sniff(path, &head) {
+            Detected::Bits => {}
+            Detected::Pcap => bail!(
+                "{} is a packet capture; extract the demodulator's UDP payloads first",
+                path.display()
+            ),
+            Detected::SigMf => bail!(
+                "{} is a SigMF recording of samples; demodulate it to a bit stream first",
+                path.display()
+            ),
+            Detected::Iq => bail!(
+                "{} looks like cf32 IQ samples; demodulate them to a bit stream first, or pass \
+                 --input-format bits if it is one",
+                path.display()
+            ),
+        }
+    }
+    FileSource::open(path)
+        .await
+        .with_context(|| format!("{}", path.display()))
+}
+
+/// When the recording at `path` started, assuming it was written live and without gaps until
+/// its last modification.
+pub fn recording_start(path: &Path) -> Result<SystemTime> {
+    let metadata = fs::metadata(path).with_context(|| format!("{}", path.display()))?;
+    let length = CaptureClock::duration(metadata.len() * 8);
+    Ok(metadata.modified()? - length)
+}
+
+/// Where the position reached in the capture at `path` is saved when decoding is interrupted.
+fn resume_file(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".pos");
+    name.into()
+}
+
+/// The byte offset saved by [`save_position`] for `path`.
+pub fn saved_position(path: &Path) -> Result<u64> {
+    let file = resume_file(path);
+    let position = fs::read_to_string(&file)
+        .with_context(|| format!("no saved position for {}", path.display()))?;
+    position
+        .trim()
+        .parse()
+        .with_context(|| format!("{}", file.display()))
+}
+
+/// Saves the byte offset decoding of `path` should resume at, or forgets it once the whole file
+/// is done.
+pub fn save_position(path: &Path, position: Option<u64>) -> Result<()> {
+    let file = resume_file(path);
+    let result = match position {
+        Some(position) => fs::write(&file, format!("{position}\n")),
+        None => match fs::remove_file(&file) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        },
+    };
+    result.with_context(|| format!("{}", file.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use dectdump::{
+        modulate::{write_cf32, Modulator, BIT_RATE},
+        synth::Synth,
+    };
+
+    use super::{sniff, Detected, HEAD_LEN};
+
+    #[test]
+    fn test_sniff() {
+        let path = Path::new("capture.bin");
+        let bits = Synth::new(1).with_slots(240).generate();
+        let samples = Modulator::new(2.0 * BIT_RATE).modulate(&bits.bits);
+        let bits = bits.into_bytes();
+        let mut iq = Vec::new();
+        write_cf32(&samples, &mut iq).unwrap();
+
+        assert_eq!(sniff(path, &bits[..HEAD_LEN]), Detected::Bits);
+        assert_eq!(sniff(path, &iq[..HEAD_LEN]), Detected::Iq);
+        assert_eq!(sniff(path, &[0; HEAD_LEN]), Detected::Bits);
+        assert_eq!(sniff(path, &[0xd4, 0xc3, 0xb2, 0xa1, 2, 0]), Detected::Pcap);
+        assert_eq!(sniff(path, &[0x0a, 0x0d, 0x0d, 0x0a]), Detected::Pcap);
+        assert_eq!(sniff(Path::new("x.sigmf-data"), &iq), Detected::SigMf);
+    }
+}
@@ -0,0 +1,74 @@
+//! Decoder for DECT bursts in a demodulated bit stream.
+//!
+//! Feed raw bits (MSB first, as they came off the air) to a [`Decoder`](decoder::Decoder) and
+//! get [`Packet`](decoder::Packet)s back. There are three ways to drive it:
+//!
+//! - [`decoder::decode_all`] for a complete capture in memory,
+//! - [`Decoder::push`](decoder::Decoder::push) for input arriving in pieces, without a runtime,
+//! - [`DecoderStream`](stream::DecoderStream) over an async [`DataSource`](stream::DataSource)
+//!   such as [`UdpSource`](source::UdpSource).
+//!
+//! ```
+//! use dectdump::decoder::{decode_all, BitIterator, Decoder, Packet};
+//!
+//! # let capture: &[u8] = &[];
+//! for packet in decode_all(capture)? {
+//!     if let Packet::A { tail, .. } = packet {
+//!         println!("{tail:02x?}");
+//!     }
+//! }
+//!
+//! let mut decoder = Decoder::new(BitIterator::new([])).with_error_correction(true);
+//! for chunk in capture.chunks(256) {
+//!     for packet in decoder.push(chunk)? {
+//!         println!("{packet:?}");
+//!     }
+//! }
+//! # Ok::<(), dectdump::error::DecodeError>(())
+//! ```
+//!
+//! # Features
+//!
+//! - `std` (default): async streams, I/O errors, packet [`clock`]s and the GFSK modulator in
+//!   [`modulate`]. Without it the crate is `no_std`.
+//! - `alloc` (implied by `std`): the [`decoder`], [`encode`] and [`synth`]. With neither, only
+//!   [`crc`], [`region`], [`scramble`] and [`error`] remain.
+//! - `net` (default): the UDP, TCP and file sources in [`source`], using tokio.
+//! - `cli` (default): the dependencies of the `dectdump` binary.
+//! - `simd`: AVX2 sync pre-scan on x86_64.
+//! - `ffi`: a C API in the `ffi` module, declared in `include/dectdump.h`. The `dectdump-ffi`
+//!   crate builds it as a shared library.
+//! - `wasm`: a JavaScript API in the `wasm` module, for `wasm32-unknown-unknown` builds.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod clock;
+pub mod crc;
+#[cfg(feature = "alloc")]
+pub mod decoder;
+#[cfg(feature = "alloc")]
+pub mod encode;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod modulate;
+pub mod region;
+pub mod scramble;
+#[cfg(feature = "net")]
+pub mod source;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "alloc")]
+pub mod synth;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
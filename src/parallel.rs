@@ -0,0 +1,258 @@
+use std::{collections::VecDeque, io::SeekFrom, ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::{Context, Error, Result};
+use dectdump::{
+    clock::{CaptureClock, Clock},
+    decoder::{DecodedPacket, Decoder, Packet, Timing, FRAME_BITS, SLOT_BITS},
+    stream::DecoderStream,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+    task::JoinHandle,
+};
+use tokio_stream::StreamExt;
+
+use crate::queue::Sender;
+
+/// Bytes of the file each task decodes for `--jobs`.
+pub const PIECE_LEN: u64 = 4 << 20;
+/// Bytes read before a piece, for the decoder to find the bursts and frame timing there.
+const LEAD_IN: u64 = 4 * 16 * FRAME_BITS / 8;
+/// Bytes read after a piece, to finish the last burst starting in it.
+const TAIL: u64 = SLOT_BITS / 8 + 8;
+
+/// A capture file decoded in pieces by several tasks at once, for `--jobs`. Packets are tagged
+/// as channel 0, like those of a file decoded as a [`Channel`](crate::channel::Channel).
+///
+/// Every piece starts a few multiframes early and keeps only the bursts whose S-field lies in
+/// it, so the pieces overlap without repeating a packet. Packets come out in file order, as a
+/// single decoder reading the file would have returned them.
+pub struct ParallelFile {
+    path: PathBuf,
+    /// Byte range of the file to decode; packet positions count from its start.
+    range: Range<u64>,
+    jobs: usize,
+    piece_len: u64,
+    decoder: Box<dyn Fn() -> Decoder + Send + Sync>,
+    carrier: Option<u8>,
+    clock: CaptureClock,
+}
+
+impl ParallelFile {
+    /// Decodes `range` of the file at `path` in pieces of `piece_len` bytes, up to `jobs` at a
+    /// time, each with a decoder from `decoder`. Packets are timed by `clock`.
+    pub fn new(
+        path: PathBuf,
+        range: Range<u64>,
+        piece_len: u64,
+        jobs: usize,
+        decoder: impl Fn() -> Decoder + Send + Sync + 'static,
+        clock: CaptureClock,
+    ) -> Self {
+        Self {
+            path,
+            range,
+            jobs: jobs.max(1),
+            piece_len: piece_len.max(1),
+            decoder: Box::new(decoder),
+            carrier: None,
+            clock,
+        }
+    }
+
+    /// Sets the RF carrier packets are tagged with.
+    pub fn with_carrier(mut self, carrier: Option<u8>) -> Self {
+        self.carrier = carrier;
+        self
+    }
+
+    /// Decodes until the end of the range, a read error, or until the receiving end is dropped.
+    ///
+    /// At most `jobs` pieces are decoded or waiting to be sent at a time. A read error ends
+    /// decoding and is forwarded as the last item, like [`Channel::run`](crate::channel::Channel::run)
+    /// does.
+    pub async fn run(self, tx: Sender<Result<DecodedPacket>>) {
+        let this = Arc::new(self);
+        let mut merge = Merge {
+            tx,
+            timing: Timing::default(),
+        };
+        let mut starts = (this.range.start..this.range.end).step_by(this.piece_len as usize);
+        let mut running: VecDeque<JoinHandle<Result<Vec<DecodedPacket>>>> = VecDeque::new();
+        loop {
+            while running.len() < this.jobs {
+                let Some(start) = starts.next() else {
+                    break;
+                };
+                running.push_back(tokio::spawn(this.clone().decode(start)));
+            }
+            let Some(piece) = running.pop_front() else {
+                return;
+            };
+            let packets = match piece.await {
+                Ok(packets) => packets,
+                Err(e) => Err(Error::from(e)),
+            };
+            let sent = match packets {
+                Ok(packets) => merge.send(packets).await,
+                Err(e) => {
+                    let e = e.context(format!("channel 0 ({})", this.path.display()));
+                    let _ = merge.tx.send(Err(e)).await;
+                    false
+                }
+            };
+            if !sent {
+                running.iter().for_each(JoinHandle::abort);
+                return;
+            }
+        }
+    }
+
+    /// The packets of the bursts starting in the piece at byte `start`.
+    async fn decode(self: Arc<Self>, start: u64) -> Result<Vec<DecodedPacket>> {
+        let end = (start + self.piece_len).min(self.range.end);
+        let read_from = start.saturating_sub(LEAD_IN).max(self.range.start);
+        let read_to = (end + TAIL).min(self.range.end);
+
+        let mut file = File::open(&self.path).await?;
+        file.seek(SeekFrom::Start(read_from)).await?;
+        let mut data = vec![0; (read_to - read_from) as usize];
+        file.read_exact(&mut data)
+            .await
+            .with_context(|| format!("reading bytes {read_from} to {read_to}"))?;
+
+        // In chunks as large as the file source reads, so the buffer limit applies the same way.
+        let chunks: Vec<_> = data.chunks(64 * 1024).map(|c| Ok(c.to_vec())).collect();
+        let mut stream = DecoderStream::new(tokio_stream::iter(chunks), (self.decoder)());
+        if let Some(carrier) = self.carrier {
+            stream = stream.with_carrier(carrier);
+        }
+
+        let offset = (read_from - self.range.start) * 8;
+        let owned = (start - self.range.start) * 8..(end - self.range.start) * 8;
+        let mut packets = Vec::new();
+        while let Some(packet) = stream.next().await {
+            let mut packet = packet?;
+            packet.position += offset;
+            if owned.contains(&packet.position) {
+                packet.received = self.clock.time(packet.position);
+                packets.push(packet);
+            }
+        }
+        Ok(packets)
+    }
+}
+
+/// Passes on the packets of one piece after another.
+///
+/// The decoder of a piece counts frames and slots from the first burst it finds, and reports
+/// gaps only since then, so both are worked out again here across the whole file.
+struct Merge {
+    tx: Sender<Result<DecodedPacket>>,
+    timing: Timing,
+}
+
+impl Merge {
+    /// Sends the packets of the next piece in order; false once the receiving end is gone.
+    async fn send(&mut self, packets: Vec<DecodedPacket>) -> bool {
+        for mut packet in packets {
+            let gap = match packet.packet {
+                Packet::Gap { dropped: false, .. } => continue,
+                Packet::A { .. } => self.timing.gap(packet.position),
+                _ => None,
+            };
+            let time = self.timing.track(&packet);
+            packet.frame = time.map(|(frame, _)| frame);
+            packet.slot = time.map(|(_, slot)| slot);
+
+            if let Some(bits) = gap {
+                let gap = DecodedPacket {
+                    packet: Packet::Gap {
+                        bits,
+                        dropped: false,
+                    },
+                    ..packet
+                };
+                if self.tx.send(Ok(gap)).await.is_err() {
+                    return false;
+                }
+            }
+            if self.tx.send(Ok(packet)).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, time::UNIX_EPOCH};
+
+    use dectdump::{
+        clock::CaptureClock,
+        decoder::{BitIterator, DecodedPacket, Decoder},
+        stream::DecoderStream,
+        synth::Synth,
+    };
+    use tokio_stream::StreamExt;
+
+    use super::ParallelFile;
+    use crate::queue::{self, Overflow};
+
+    fn summary(packet: &DecodedPacket) -> String {
+        format!(
+            "{} {:?} {:?} {:?} {:#}",
+            packet.position, packet.frame, packet.slot, packet.received, packet.packet
+        )
+    }
+
+    #[tokio::test]
+    async fn test_parallel_file() {
+        // Five multiframes of bursts, a silence longer than the lead-in, and five more, in pieces
+        // that cut through some of the bursts.
+        let multiframes = |seed| {
+            Synth::new(seed)
+                .with_slots(5 * 16 * 24)
+                .generate()
+                .into_bytes()
+        };
+        let mut bytes = multiframes(7);
+        bytes.extend(vec![0; 2 * super::LEAD_IN as usize]);
+        bytes.extend(multiframes(8));
+        let path = std::env::temp_dir().join(format!("dectdump-parallel-{}", std::process::id()));
+        fs::write(&path, &bytes).unwrap();
+        let clock = CaptureClock::new(UNIX_EPOCH);
+
+        // The same bytes from the middle of the file, in one piece.
+        let skip = 1000;
+        let source = tokio_stream::iter([Ok(bytes[skip..].to_vec())]);
+        let decoder = Decoder::new(BitIterator::new([]));
+        let expected: Vec<_> = DecoderStream::new(source, decoder)
+            .with_clock(clock.clone())
+            .map(|packet| summary(&packet.unwrap()))
+            .collect()
+            .await;
+
+        let file = ParallelFile::new(
+            path.clone(),
+            skip as u64..bytes.len() as u64,
+            10_007,
+            3,
+            || Decoder::new(BitIterator::new([])),
+            clock,
+        );
+        let (tx, mut rx) = queue::bounded(16, Overflow::Block);
+        tokio::spawn(file.run(tx));
+        let mut packets = Vec::new();
+        while let Some(packet) = rx.recv().await {
+            packets.push(summary(&packet.unwrap()));
+        }
+        fs::remove_file(path).unwrap();
+
+        assert!(expected.len() > 1000);
+        assert!(expected.iter().any(|p| p.contains("not decoded")));
+        assert_eq!(packets, expected);
+    }
+}
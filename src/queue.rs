@@ -0,0 +1,203 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use clap::ValueEnum;
+use tokio::sync::Notify;
+
+/// What a sender does when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Overflow {
+    /// Wait for the receiver to make room. Input piles up in the socket and decoder buffers,
+    /// which are bounded themselves.
+    Block,
+    /// Discard the oldest queued item and count it.
+    DropOldest,
+}
+
+#[derive(Debug)]
+struct State<T> {
+    items: VecDeque<T>,
+    senders: usize,
+    closed: bool,
+    dropped: u64,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+    overflow: Overflow,
+    /// Signalled when an item is pushed or the last sender goes away.
+    items: Notify,
+    /// Signalled when an item is popped or the receiver goes away.
+    space: Notify,
+}
+
+/// Creates a queue holding at most `capacity` items, with many senders and one receiver.
+pub fn bounded<T>(capacity: usize, overflow: Overflow) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            items: VecDeque::with_capacity(capacity),
+            senders: 1,
+            closed: false,
+            dropped: 0,
+        }),
+        capacity: capacity.max(1),
+        overflow,
+        items: Notify::new(),
+        space: Notify::new(),
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+#[derive(Debug)]
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Queues `value`, applying the overflow policy if the queue is full.
+    ///
+    /// Hands the value back if the receiver is gone.
+    pub async fn send(&self, value: T) -> Result<(), T> {
+        loop {
+            let notified = {
+                let mut state = self.shared.state.lock().unwrap();
+                if state.closed {
+                    return Err(value);
+                }
+
+                let full = state.items.len() >= self.shared.capacity;
+                if full && self.shared.overflow == Overflow::Block {
+                    // Created under the lock so a wakeup can't slip in before the await.
+                    self.shared.space.notified()
+                } else {
+                    if full {
+                        state.items.pop_front();
+                        state.dropped += 1;
+                    }
+                    state.items.push_back(value);
+                    self.shared.items.notify_one();
+                    return Ok(());
+                }
+            };
+            notified.await;
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.shared.items.notify_one();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Takes the oldest item, or returns `None` once the queue is empty and all senders are gone.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            let notified = {
+                let mut state = self.shared.state.lock().unwrap();
+                if let Some(value) = state.items.pop_front() {
+                    self.shared.space.notify_one();
+                    return Some(value);
+                }
+                if state.senders == 0 {
+                    return None;
+                }
+                self.shared.items.notified()
+            };
+            notified.await;
+        }
+    }
+
+    /// Number of items discarded so far under [`Overflow::DropOldest`].
+    pub fn dropped(&self) -> u64 {
+        self.shared.state.lock().unwrap().dropped
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.space.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{bounded, Overflow};
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let (tx, mut rx) = bounded(2, Overflow::DropOldest);
+        for i in 0..5 {
+            tx.send(i).await.unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(rx.dropped(), 3);
+        assert_eq!(rx.recv().await, Some(3));
+        assert_eq!(rx.recv().await, Some(4));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_block() {
+        let (tx, mut rx) = bounded(2, Overflow::Block);
+        let producer = tokio::spawn(async move {
+            for i in 0..5 {
+                tx.send(i).await.unwrap();
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!producer.is_finished());
+
+        for i in 0..5 {
+            assert_eq!(rx.recv().await, Some(i));
+        }
+        assert_eq!(rx.recv().await, None);
+        assert_eq!(rx.dropped(), 0);
+        producer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_receiver_gone() {
+        let (tx, rx) = bounded(1, Overflow::Block);
+        tx.send(1).await.unwrap();
+        let blocked = tokio::spawn(async move { tx.send(2).await });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(rx);
+        assert_eq!(blocked.await.unwrap(), Err(2));
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    fs, io,
+    path::Path,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use dectdump::stream::DataSource;
+use futures_core::Stream;
+use tokio::{fs::File, io::AsyncWrite};
+
+/// Passes a source's input through while appending it to a file, so it can be decoded again
+/// later with `--input`.
+///
+/// The input is written before the decoder sees it, so the file is complete even when the
+/// decoder falls behind and drops bits. Writes run on tokio's blocking threads: a slow disk holds
+/// up this channel, whose input then waits in the socket, but not the other channels.
+///
+/// Source errors are passed on as they are, so the channel goes on after those a socket recovers
+/// from. A failed write ends the channel like any other input error.
+#[derive(Debug)]
+pub struct Recorder<S> {
+    source: S,
+    file: File,
+    /// A chunk being written, and how much of it is done.
+    pending: Option<(Vec<u8>, usize)>,
+    /// The source has ended.
+    done: bool,
+}
+
+impl<S> Recorder<S> {
+    /// Records everything `source` yields to a new file at `path`.
+    pub fn create(source: S, path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            source,
+            file: File::from_std(fs::File::create(path)?),
+            pending: None,
+            done: false,
+        })
+    }
+}
+
+impl<S: DataSource> Stream for Recorder<S> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some((chunk, written)) = &mut this.pending {
+                while *written < chunk.len() {
+                    let n = ready!(Pin::new(&mut this.file).poll_write(cx, &chunk[*written..]))?;
+                    if n == 0 {
+                        return Poll::Ready(Some(Err(io::ErrorKind::WriteZero.into())));
+                    }
+                    *written += n;
+                }
+                let (chunk, _) = this.pending.take().unwrap();
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+
+            if this.done {
+                // Wait for the last write to reach the file.
+                ready!(Pin::new(&mut this.file).poll_flush(cx))?;
+                return Poll::Ready(None);
+            }
+            match ready!(Pin::new(&mut this.source).poll_next(cx)) {
+                Some(Ok(chunk)) => this.pending = Some((chunk, 0)),
+                None => this.done = true,
+                error => return Poll::Ready(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io};
+
+    use tokio_stream::StreamExt;
+
+    use super::Recorder;
+
+    #[tokio::test]
+    async fn test_recorder() {
+        let path = std::env::temp_dir().join(format!("dectdump-record-{}", std::process::id()));
+        // A refused UDP receive, which the channel goes on from.
+        let refused = io::Error::from(io::ErrorKind::ConnectionRefused);
+        let chunks = [Ok(vec![1, 2]), Err(refused), Ok(vec![3])];
+        let recorder = Recorder::create(tokio_stream::iter(chunks), &path).unwrap();
+        let items: Vec<_> = recorder.collect().await;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2].as_ref().unwrap(), &[3]);
+        assert_eq!(fs::read(&path).unwrap(), [1, 2, 3]);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+//! Regional DECT frequency plans: which RF carriers exist and where they sit.
+//!
+//! Carriers are 1.728 MHz apart and numbered from the top of the band down, as in the European
+//! plan, where carrier 0 is at 1897.344 MHz.
+
+/// Distance between neighbouring carriers, in Hz.
+pub const CARRIER_SPACING: u32 = 1_728_000;
+
+/// A regional frequency plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Region {
+    /// Europe and most of the world: 10 carriers, 1881.792 to 1897.344 MHz.
+    #[default]
+    Eu,
+    /// DECT 6.0 in North America: 5 carriers, 1921.536 to 1928.448 MHz.
+    Us,
+    /// J-DECT in Japan: 5 carriers, 1895.616 to 1902.528 MHz.
+    Japan,
+}
+
+impl Region {
+    /// Frequency of carrier 0, in Hz.
+    const fn top(self) -> u32 {
+        match self {
+            Region::Eu => 1_897_344_000,
+            Region::Us => 1_928_448_000,
+            Region::Japan => 1_902_528_000,
+        }
+    }
+
+    /// Number of carriers in the plan.
+    pub const fn carriers(self) -> u8 {
+        match self {
+            Region::Eu => 10,
+            Region::Us | Region::Japan => 5,
+        }
+    }
+
+    /// Centre frequency of `carrier` in Hz, or `None` if the plan has no such carrier.
+    pub const fn frequency(self, carrier: u8) -> Option<u32> {
+        if carrier >= self.carriers() {
+            return None;
+        }
+        Some(self.top() - carrier as u32 * CARRIER_SPACING)
+    }
+
+    /// The carrier whose channel contains `frequency` (in Hz), if any.
+    pub const fn carrier_at(self, frequency: u32) -> Option<u8> {
+        let half = CARRIER_SPACING / 2;
+        if frequency > self.top() + half {
+            return None;
+        }
+        let carrier = (self.top() + half - frequency) / CARRIER_SPACING;
+        if carrier >= self.carriers() as u32 {
+            return None;
+        }
+        Some(carrier as u8)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Region;
+
+    #[test]
+    fn test_frequencies() {
+        assert_eq!(Region::Eu.frequency(0), Some(1_897_344_000));
+        assert_eq!(Region::Eu.frequency(9), Some(1_881_792_000));
+        assert_eq!(Region::Eu.frequency(10), None);
+        assert_eq!(Region::Us.frequency(4), Some(1_921_536_000));
+        assert_eq!(Region::Us.frequency(5), None);
+        assert_eq!(Region::Japan.frequency(4), Some(1_895_616_000));
+    }
+
+    #[test]
+    fn test_carrier_at() {
+        for region in [Region::Eu, Region::Us, Region::Japan] {
+            for carrier in 0..region.carriers() {
+                let frequency = region.frequency(carrier).unwrap();
+                assert_eq!(region.carrier_at(frequency), Some(carrier));
+                assert_eq!(region.carrier_at(frequency + 800_000), Some(carrier));
+                assert_eq!(region.carrier_at(frequency - 800_000), Some(carrier));
+            }
+        }
+        assert_eq!(Region::Eu.carrier_at(1_900_000_000), None);
+        assert_eq!(Region::Eu.carrier_at(1_880_000_000), None);
+        assert_eq!(Region::Us.carrier_at(1_897_344_000), None);
+    }
+}
@@ -0,0 +1,77 @@
+//! The B-field scrambler, keyed by frame number.
+//!
+//! DECT XORs every B-field bit with a pseudo-random sequence from a 7-stage shift register,
+//! g(x) = x^7 + x^4 + 1, restarted for each burst. The register starts as four ones followed by
+//! the three least significant bits of the frame number, so there are eight sequences. Scrambling
+//! is its own inverse: the encoder and decoder both use [`scramble`].
+
+use bitvec::{order::Msb0, slice::BitSlice};
+
+/// The scrambling sequence for one burst, one bit at a time. It repeats every 127 bits.
+#[derive(Debug, Clone)]
+pub struct Scrambler {
+    state: u8,
+}
+
+impl Scrambler {
+    /// The sequence for bursts in frame `frame` (only the low three bits matter).
+    pub const fn new(frame: u8) -> Self {
+        Self {
+            state: 0x78 | (frame & 7),
+        }
+    }
+}
+
+impl Iterator for Scrambler {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        let out = self.state >> 6 & 1;
+        let feedback = (self.state >> 6 ^ self.state >> 3) & 1;
+        self.state = (self.state << 1 | feedback) & 0x7f;
+        Some(out != 0)
+    }
+}
+
+/// Scrambles or descrambles `bits` in place for a burst in frame `frame`.
+pub fn scramble(frame: u8, bits: &mut BitSlice<u8, Msb0>) {
+    for (mut bit, key) in bits.iter_mut().zip(Scrambler::new(frame)) {
+        *bit ^= key;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitvec::{bitarr, order::Msb0};
+
+    use super::{scramble, Scrambler};
+
+    #[test]
+    fn test_sequence() {
+        for frame in 0..8 {
+            let sequence: [bool; 254] = core::array::from_fn({
+                let mut scrambler = Scrambler::new(frame);
+                move |_| scrambler.next().unwrap()
+            });
+            // Maximal length: the register runs through all 127 non-zero states.
+            assert_eq!(sequence[..127], sequence[127..]);
+            assert!((1..127).all(|shift| sequence[shift..shift + 127] != sequence[..127]));
+            assert_eq!(sequence.iter().filter(|&&bit| bit).count(), 2 * 64);
+        }
+
+        // Only the low three bits of the frame number are used.
+        assert!(Scrambler::new(3).take(64).eq(Scrambler::new(11).take(64)));
+        assert!(!Scrambler::new(3).take(64).eq(Scrambler::new(4).take(64)));
+    }
+
+    #[test]
+    fn test_scramble_is_self_inverse() {
+        let original = bitarr![u8, Msb0; 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 1, 1, 1, 0, 0, 1];
+        let mut bits = original;
+
+        scramble(5, &mut bits);
+        assert_ne!(bits, original);
+        scramble(5, &mut bits);
+        assert_eq!(bits, original);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{env, io, process, time::Duration};
+
+use tokio::signal;
+
+/// Reports to a service manager such as systemd over `$NOTIFY_SOCKET`, like `sd_notify(3)`.
+#[derive(Debug)]
+pub struct Notifier {
+    socket: String,
+    watchdog: Option<Duration>,
+}
+
+impl Notifier {
+    /// The service manager's notification socket, if we were started by one that wants
+    /// notifications.
+    pub fn from_env() -> Option<Self> {
+        let socket = env::var("NOTIFY_SOCKET").ok()?;
+        // A watchdog set up for another process of the service is not ours to feed.
+        let ours = env::var("WATCHDOG_PID").map_or(true, |pid| pid.parse() == Ok(process::id()));
+        let watchdog = env::var("WATCHDOG_USEC")
+            .ok()
+            .filter(|_| ours)
+            .and_then(|usec| usec.parse().ok())
+            .map(Duration::from_micros);
+        Some(Self { socket, watchdog })
+    }
+
+    /// How often to send `WATCHDOG=1`, if the watchdog is enabled: half its timeout.
+    pub fn watchdog_interval(&self) -> Option<Duration> {
+        self.watchdog.map(|timeout| timeout / 2)
+    }
+
+    /// Sends a state such as `READY=1`. Failures are only reported, since decoding can go on
+    /// without the service manager.
+    pub fn notify(&self, state: &str) {
+        if let Err(e) = send(&self.socket, state) {
+            eprintln!("cannot notify service manager at {}: {e}", self.socket);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn send(socket: &str, state: &str) -> io::Result<()> {
+    use std::os::{
+        linux::net::SocketAddrExt,
+        unix::net::{SocketAddr, UnixDatagram},
+    };
+
+    // A leading `@` names a socket in the abstract namespace.
+    let addr = match socket.strip_prefix('@') {
+        Some(name) => SocketAddr::from_abstract_name(name)?,
+        None => SocketAddr::from_pathname(socket)?,
+    };
+    UnixDatagram::unbound()?.send_to_addr(state.as_bytes(), &addr)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send(_socket: &str, _state: &str) -> io::Result<()> {
+    Ok(())
+}
+
+/// Completes on Ctrl-C or, on Unix, SIGTERM: the signals that ask for a clean shutdown.
+pub async fn shutdown() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    signal::ctrl_c().await
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::{env, os::unix::net::UnixDatagram, process, time::Duration};
+
+    use super::Notifier;
+
+    #[test]
+    fn test_notify() {
+        let path = env::temp_dir().join(format!("dectdump-notify-{}", process::id()));
+        let receiver = UnixDatagram::bind(&path).unwrap();
+        let notifier = Notifier {
+            socket: path.to_str().unwrap().to_string(),
+            watchdog: Some(Duration::from_secs(30)),
+        };
+        notifier.notify("READY=1");
+
+        let mut buf = [0; 64];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1");
+        assert_eq!(notifier.watchdog_interval(), Some(Duration::from_secs(15)));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+//! AVX2 pre-scan for sync words, checking 64 bit offsets per step.
+
+use std::arch::x86_64::*;
+
+/// Returns the first byte index `j >= start` such that a sync word may begin at a bit offset in
+/// `j * 8..j * 8 + 64`, or the index at which too little data was left to scan in bulk.
+///
+/// Every sync start before the returned byte (and at or after `start`) is guaranteed not to
+/// match, so the caller only has to run its exact scan from there. Falls back to returning
+/// `start` unchanged on CPUs without AVX2.
+pub(crate) fn skip(data: &[u8], start: usize, syncs: &[u32]) -> usize {
+    if !is_x86_feature_detected!("avx2") {
+        return start;
+    }
+
+    // SAFETY: AVX2 support was checked above.
+    unsafe { skip_avx2(data, start, syncs) }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn skip_avx2(data: &[u8], mut j: usize, syncs: &[u32]) -> usize {
+    // Lane k holds data[j + k..j + k + 4] as a big-endian u32. The shuffle works per 128-bit
+    // half, so the upper half is loaded from j + 4.
+    let shuffle = _mm256_setr_epi8(
+        3, 2, 1, 0, 4, 3, 2, 1, 5, 4, 3, 2, 6, 5, 4, 3, 3, 2, 1, 0, 4, 3, 2, 1, 5, 4, 3, 2, 6, 5,
+        4, 3,
+    );
+    let mask = _mm256_set1_epi32(0xffffff);
+
+    while j + 20 <= data.len() {
+        let lo = _mm_loadu_si128(data.as_ptr().add(j) as *const __m128i);
+        let hi = _mm_loadu_si128(data.as_ptr().add(j + 4) as *const __m128i);
+        let words = _mm256_shuffle_epi8(_mm256_set_m128i(hi, lo), shuffle);
+
+        let mut hit = _mm256_setzero_si256();
+        for bit in 0..8 {
+            // The 24 bits starting `bit` bits into each lane's first byte.
+            let shifted = _mm256_srlv_epi32(words, _mm256_set1_epi32(8 - bit));
+            let candidate = _mm256_and_si256(shifted, mask);
+            for &sync in syncs {
+                let sync = _mm256_set1_epi32(sync as i32);
+                hit = _mm256_or_si256(hit, _mm256_cmpeq_epi32(candidate, sync));
+            }
+        }
+
+        if _mm256_testz_si256(hit, hit) == 0 {
+            return j;
+        }
+        j += 8;
+    }
+
+    j
+}
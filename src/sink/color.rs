@@ -0,0 +1,104 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+use clap::ValueEnum;
+use dectdump::decoder::{Packet, Sender, TailType};
+
+/// Whether to color the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// When stdout is a terminal and `NO_COLOR` is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// The ANSI style for a packet: gaps red, repaired A-fields yellow, other A-fields by tail type,
+/// bold when sent by a fixed part. `sender` is who sent the burst, going by its sync word.
+pub fn style(packet: &Packet, sender: Option<Sender>) -> String {
+    let color = match packet {
+        Packet::Header { .. } => "2",
+        Packet::Gap { .. } => "31",
+        Packet::A {
+            corrected: true, ..
+        } => "33",
+        Packet::A { header, .. } => match TailType::from_header(*header) {
+            TailType::Nt | TailType::NtConnectionless => "32",
+            TailType::Qt => "36",
+            TailType::Mt => "35",
+            TailType::Pt => "34",
+            TailType::Ct0 | TailType::Ct1 | TailType::Escape => "39",
+        },
+    };
+    let bold = if sender == Some(Sender::FixedPart) && matches!(packet, Packet::A { .. }) {
+        "1;"
+    } else {
+        ""
+    };
+    format!("\x1b[{bold}{color}m")
+}
+
+/// `text` in `style`, reset afterwards.
+pub fn paint(text: &str, style: &str) -> String {
+    format!("{style}{text}{RESET}")
+}
+
+#[cfg(test)]
+mod test {
+    use dectdump::decoder::{Packet, Sender};
+
+    use super::{paint, style};
+
+    fn a(header: u8, corrected: bool) -> Packet {
+        Packet::A {
+            header,
+            tail: [0; 5],
+            crc: 0,
+            b: None,
+            corrected,
+        }
+    }
+
+    #[test]
+    fn test_style() {
+        let fp = Some(Sender::FixedPart);
+        assert_eq!(style(&a(3 << 5, false), fp), "\x1b[1;32m");
+        assert_eq!(
+            style(&a(3 << 5, false), Some(Sender::PortablePart)),
+            "\x1b[32m"
+        );
+        assert_eq!(style(&a(4 << 5, true), None), "\x1b[33m");
+        let gap = Packet::Gap {
+            bits: 1,
+            dropped: false,
+        };
+        assert_eq!(style(&gap, fp), "\x1b[31m");
+        assert_eq!(paint("A", "\x1b[32m"), "\x1b[32mA\x1b[0m");
+
+        // Headers stay dim whoever sent them.
+        let header = Packet::Header {
+            rxmode: 0,
+            channel: 0,
+            slot: 0,
+            frameno: 0,
+            rssi: 0,
+            preamble: [0; 3],
+            sync: 0xe98a_u16.to_be(),
+        };
+        assert_eq!(style(&header, fp), "\x1b[2m");
+    }
+}
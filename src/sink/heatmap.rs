@@ -0,0 +1,219 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use dectdump::decoder::{DecodedPacket, Packet};
+
+use crate::sink::{received, PacketSink};
+
+/// Output format of a [`Heatmap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HeatmapFormat {
+    /// `time,carrier,slot,bursts` with a header line.
+    Csv,
+    /// One JSON object per line with the same keys, plus `schema` from schema version 1 on.
+    Json,
+}
+
+/// Current version of the JSON heatmap rows, written to each row as `schema`. Bumped whenever a
+/// key changes meaning or goes away; consumers should ignore keys they do not know.
+pub const HEATMAP_SCHEMA: u32 = 1;
+
+/// Counts bursts per carrier and slot in fixed time buckets and writes one row per occupied
+/// carrier/slot pair whenever a bucket is complete.
+///
+/// Only bursts that passed their CRC count. Unknown carriers and slots are left empty (`null`
+/// in JSON).
+pub struct Heatmap<W: Write> {
+    out: W,
+    format: HeatmapFormat,
+    schema: u32,
+    bucket: Duration,
+    /// Index of the bucket being counted, in multiples of `bucket` since the Unix epoch.
+    current: Option<u128>,
+    counts: BTreeMap<(Option<u8>, Option<u8>), u64>,
+}
+
+impl Heatmap<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>, format: HeatmapFormat, bucket: Duration) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), format, bucket)
+    }
+}
+
+impl<W: Write> Heatmap<W> {
+    pub fn new(mut out: W, format: HeatmapFormat, bucket: Duration) -> Result<Self> {
+        anyhow::ensure!(!bucket.is_zero(), "heatmap interval must not be zero");
+        if format == HeatmapFormat::Csv {
+            writeln!(out, "time,carrier,slot,bursts")?;
+        }
+        Ok(Self {
+            out,
+            format,
+            schema: HEATMAP_SCHEMA,
+            bucket,
+            current: None,
+            counts: BTreeMap::new(),
+        })
+    }
+
+    /// Writes JSON rows in an older schema version, for consumers that have not caught up.
+    /// Version 0 is the format from before rows were versioned.
+    pub fn with_schema(mut self, schema: u32) -> Result<Self> {
+        anyhow::ensure!(
+            schema <= HEATMAP_SCHEMA,
+            "heatmap schema {schema} is newer than {HEATMAP_SCHEMA}"
+        );
+        self.schema = schema;
+        Ok(self)
+    }
+
+    /// Writes the rows of the current bucket.
+    fn flush_bucket(&mut self) -> Result<()> {
+        let Some(index) = self.current else {
+            return Ok(());
+        };
+        let start = UNIX_EPOCH + Duration::from_nanos((index * self.bucket.as_nanos()) as u64);
+        let time = humantime::format_rfc3339_micros(start);
+        let schema = match self.schema {
+            0 => String::new(),
+            schema => format!(r#""schema":{schema},"#),
+        };
+
+        for ((carrier, slot), bursts) in std::mem::take(&mut self.counts) {
+            match self.format {
+                HeatmapFormat::Csv => writeln!(
+                    self.out,
+                    "{time},{},{},{bursts}",
+                    field(carrier, ""),
+                    field(slot, "")
+                )?,
+                HeatmapFormat::Json => writeln!(
+                    self.out,
+                    r#"{{{schema}"time":"{time}","carrier":{},"slot":{},"bursts":{bursts}}}"#,
+                    field(carrier, "null"),
+                    field(slot, "null")
+                )?,
+            }
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+fn field(value: Option<u8>, missing: &str) -> String {
+    value.map_or_else(|| missing.to_string(), |value| value.to_string())
+}
+
+#[async_trait]
+impl<W: Write + Send> PacketSink for Heatmap<W> {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()> {
+        if !matches!(packet.packet, Packet::A { .. }) {
+            return Ok(());
+        }
+
+        let since_epoch = received(packet).duration_since(UNIX_EPOCH)?;
+        let index = since_epoch.as_nanos() / self.bucket.as_nanos();
+        if self.current != Some(index) {
+            self.flush_bucket()?;
+            self.current = Some(index);
+        }
+
+        let slot = packet.slot.map(|slot| slot.number);
+        *self.counts.entry((packet.carrier, slot)).or_default() += 1;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        self.flush_bucket()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use dectdump::decoder::{DecodedPacket, Packet, Slot, SyncWords};
+
+    use super::{Heatmap, HeatmapFormat, HEATMAP_SCHEMA};
+    use crate::sink::PacketSink;
+
+    fn burst(secs: u64, carrier: Option<u8>, slot: u8) -> DecodedPacket {
+        DecodedPacket {
+            channel: 0,
+            carrier,
+            position: 0,
+            frame: None,
+            slot: Some(Slot {
+                number: slot,
+                confirmed: false,
+            }),
+            received: Some(Duration::from_secs(secs)),
+            syncs: SyncWords::DECT,
+            packet: Packet::A {
+                header: 0,
+                tail: [0; 5],
+                crc: 0,
+                b: None,
+                corrected: false,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buckets() {
+        let mut out = Vec::new();
+        let mut heatmap =
+            Heatmap::new(&mut out, HeatmapFormat::Csv, Duration::from_secs(10)).unwrap();
+        for packet in [
+            burst(1, Some(3), 2),
+            burst(9, Some(3), 2),
+            burst(9, None, 14),
+            burst(12, Some(3), 2),
+        ] {
+            heatmap.emit(&packet).await.unwrap();
+        }
+        heatmap.finish().await.unwrap();
+        drop(heatmap);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "time,carrier,slot,bursts\n\
+             1970-01-01T00:00:00.000000Z,,14,1\n\
+             1970-01-01T00:00:00.000000Z,3,2,2\n\
+             1970-01-01T00:00:10.000000Z,3,2,1\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_schema() {
+        let mut rows = Vec::new();
+        for schema in [HEATMAP_SCHEMA, 0] {
+            let mut out = Vec::new();
+            let mut heatmap = Heatmap::new(&mut out, HeatmapFormat::Json, Duration::from_secs(1))
+                .unwrap()
+                .with_schema(schema)
+                .unwrap();
+            heatmap.emit(&burst(1, Some(3), 2)).await.unwrap();
+            heatmap.finish().await.unwrap();
+            drop(heatmap);
+            rows.push(String::from_utf8(out).unwrap());
+        }
+        assert_eq!(
+            rows,
+            [
+                "{\"schema\":1,\"time\":\"1970-01-01T00:00:01.000000Z\",\"carrier\":3,\"slot\":2,\"bursts\":1}\n",
+                "{\"time\":\"1970-01-01T00:00:01.000000Z\",\"carrier\":3,\"slot\":2,\"bursts\":1}\n"
+            ]
+        );
+
+        let heatmap = Heatmap::new(Vec::new(), HeatmapFormat::Json, Duration::from_secs(1));
+        assert!(heatmap.unwrap().with_schema(HEATMAP_SCHEMA + 1).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use std::{fmt::Write, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use dectdump::decoder::{pack_bits, DecodedPacket, Packet, Sender};
+
+use crate::sink::{color, dect_time, received, Labels, PacketSink, Senders};
+
+/// How packets are printed on stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One line per packet.
+    Text,
+    /// The raw bits of every burst in hex, one field per line, marking where the preamble, sync
+    /// word, A-field header, tail, R-CRC and B-field lie.
+    Hexdump,
+}
+
+/// Prints packets to stdout as annotated hex dumps, for seeing why a burst decodes the way it
+/// does. Labels and colors are those of [`Stdout`](crate::sink::Stdout).
+#[derive(Debug, Default)]
+pub struct Hexdump {
+    labels: Arc<Labels>,
+    color: bool,
+    senders: Senders,
+}
+
+impl Hexdump {
+    pub fn new(labels: Arc<Labels>) -> Self {
+        Self {
+            labels,
+            ..Self::default()
+        }
+    }
+
+    /// Colors each dump by packet kind, tail type, sender and CRC repair (see [`color::style`]).
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn format(&mut self, packet: &DecodedPacket) -> String {
+        let sender = self.senders.track(packet);
+        let text = render(packet, self.labels.lookup(&packet.packet, sender));
+        if !self.color {
+            return text;
+        }
+        let style = color::style(&packet.packet, sender);
+        format!("{}\n", color::paint(text.trim_end(), &style))
+    }
+}
+
+#[async_trait]
+impl PacketSink for Hexdump {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()> {
+        print!("{}", self.format(packet));
+        Ok(())
+    }
+}
+
+/// Width of the field name column.
+const NAME_WIDTH: usize = 10;
+/// Width of the hex column, enough for 16 bytes.
+const HEX_WIDTH: usize = 48;
+
+/// The dump of `packet`, with `label` next to the tail it names.
+fn render(packet: &DecodedPacket, label: Option<&str>) -> String {
+    let mut out = format!(
+        "{} {} {}\n",
+        humantime::format_rfc3339_micros(received(packet)),
+        packet.channel,
+        dect_time(packet)
+    );
+    match &packet.packet {
+        Packet::Header { preamble, sync, .. } => {
+            let sync = u16::from_be(*sync);
+            let sender = match packet.syncs.classify(sync) {
+                Some(Sender::FixedPart) => "fixed part",
+                Some(Sender::PortablePart) => "portable part",
+                None => "inexact match",
+            };
+            line(
+                &mut out,
+                "preamble",
+                preamble,
+                "24 bits before the sync word",
+            );
+            line(
+                &mut out,
+                "sync",
+                &sync.to_be_bytes(),
+                &format!("low 16 bits, {sender}"),
+            );
+        }
+        Packet::A {
+            header,
+            tail,
+            crc,
+            b,
+            corrected,
+        } => {
+            let fields = format!(
+                "TA {:03b} Q1 {:b} BA {:03b} Q2 {:b}",
+                header >> 5,
+                header >> 4 & 1,
+                header >> 1 & 7,
+                header & 1
+            );
+            line(&mut out, "header", &[*header], &fields);
+            line(&mut out, "tail", tail, label.unwrap_or_default());
+            let note = if *corrected { "one bit corrected" } else { "" };
+            line(&mut out, "r-crc", &crc.to_be_bytes(), note);
+            if let Some(b) = b {
+                let bytes = pack_bits(b);
+                for (i, row) in bytes.chunks(16).enumerate() {
+                    let name = if i == 0 { "b-field" } else { "" };
+                    let note = if i == 0 {
+                        format!("{} bits, scrambled", b.len())
+                    } else {
+                        String::new()
+                    };
+                    line(&mut out, name, row, &note);
+                }
+            }
+        }
+        Packet::Gap { bits, dropped } => {
+            let kind = if *dropped { "dropped" } else { "not decoded" };
+            let _ = writeln!(out, "  {:NAME_WIDTH$}{bits} bits {kind}", "gap");
+        }
+    }
+    out
+}
+
+fn line(out: &mut String, name: &str, bytes: &[u8], note: &str) {
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    let _ = writeln!(
+        out,
+        "  {name:NAME_WIDTH$}{:HEX_WIDTH$}{note}",
+        hex.join(" ")
+    );
+    // No trailing spaces when there is no note.
+    if note.is_empty() {
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bitvec::{bitvec, order::Msb0, vec::BitVec};
+    use dectdump::{
+        decoder::{BitIterator, DecodedPacket, Decoder, Packet, SyncWords, FP_SYNC, PP_SYNC},
+        encode::{AField, Burst},
+    };
+
+    use super::{render, Hexdump};
+    use crate::sink::Labels;
+
+    fn packet(packet: Packet) -> DecodedPacket {
+        DecodedPacket {
+            channel: 1,
+            carrier: None,
+            position: 0,
+            frame: None,
+            slot: None,
+            received: Some(Default::default()),
+            syncs: SyncWords::DECT,
+            packet,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let sync = || {
+            packet(Packet::Header {
+                rxmode: 0,
+                channel: 0,
+                slot: 0,
+                frameno: 0,
+                rssi: 0,
+                preamble: [0xaa, 0xaa, 0xaa],
+                sync: 0xe98a_u16.to_be(),
+            })
+        };
+        let header = render(&sync(), None);
+        assert_eq!(
+            header,
+            "1970-01-01T00:00:00.000000Z 1 -.-.-\n  \
+             preamble  aa aa aa                                        24 bits before the sync word\n  \
+             sync      e9 8a                                           low 16 bits, fixed part\n"
+        );
+        // Who sent it depends on the channel's sync words.
+        let mut swapped = sync();
+        swapped.syncs = SyncWords {
+            fp: PP_SYNC,
+            pp: FP_SYNC,
+        };
+        assert!(render(&swapped, None).ends_with("low 16 bits, portable part\n"));
+
+        let a = render(
+            &packet(Packet::A {
+                header: 0b0110_0110,
+                tail: [1, 2, 3, 4, 5],
+                crc: 0xbeef,
+                b: Some(BitVec::<u8, Msb0>::repeat(true, 320)),
+                corrected: true,
+            }),
+            None,
+        );
+        let lines: Vec<&str> = a.lines().collect();
+        assert_eq!(lines.len(), 1 + 3 + 3);
+        assert!(
+            lines[1].starts_with("  header    66 ")
+                && lines[1].ends_with("TA 011 Q1 0 BA 011 Q2 0")
+        );
+        assert_eq!(lines[2], "  tail      01 02 03 04 05");
+        assert!(lines[3].ends_with("one bit corrected"));
+        assert!(
+            lines[4].starts_with("  b-field   ff ff") && lines[4].ends_with("320 bits, scrambled")
+        );
+        assert_eq!(lines[6], "            ff ff ff ff ff ff ff ff");
+    }
+
+    #[test]
+    fn test_labels_and_color() {
+        let labels = Labels::parse("0101010101 office base").unwrap();
+        let mut hexdump = Hexdump::new(Arc::new(labels)).with_color(true);
+        let header = packet(Packet::Header {
+            rxmode: 0,
+            channel: 0,
+            slot: 0,
+            frameno: 0,
+            rssi: 0,
+            preamble: [0xaa, 0xaa, 0xaa],
+            sync: 0xe98a_u16.to_be(),
+        });
+        let a = packet(Packet::A {
+            header: 3 << 5,
+            tail: [1; 5],
+            crc: 0,
+            b: None,
+            corrected: false,
+        });
+        assert!(hexdump.format(&header).starts_with("\x1b[2m1970"));
+        let dump = hexdump.format(&a);
+        // Bold green: an Nt tail from a fixed part, whose RFPI has a name.
+        assert!(dump.starts_with("\x1b[1;32m1970"));
+        assert!(dump
+            .lines()
+            .any(|line| line.starts_with("  tail") && line.ends_with("office base")));
+        assert!(dump.ends_with("\x1b[0m\n"));
+
+        // Without color the dump is unchanged.
+        let mut plain = Hexdump::default();
+        plain.format(&header);
+        assert_eq!(plain.format(&a), render(&a, None));
+    }
+
+    #[test]
+    fn test_render_unaligned() {
+        // The burst starts 3 bits into a byte, so its B-field does too.
+        let mut bits = bitvec![u8, Msb0; 0; 35];
+        Burst::fp(AField::nt([1; 5]))
+            .with_b_field(BitVec::from_vec(vec![0xc3; 40]))
+            .write(&mut bits);
+        bits.resize(bits.len() + 64, false);
+
+        let mut decoder = Decoder::new(BitIterator::new([]));
+        let a = decoder.push(&bits.into_vec()).unwrap().pop().unwrap();
+        let a = render(&packet(a), None);
+        let rows: Vec<&str> = a.lines().skip(4).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("  b-field   c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 c3 "));
+        assert_eq!(rows[2], "            c3 c3 c3 c3 c3 c3 c3 c3");
+    }
+}
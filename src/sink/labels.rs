@@ -0,0 +1,113 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use dectdump::decoder::{Packet, Sender};
+
+/// Human names for fixed parts (by RFPI) and portable parts (by PMID).
+///
+/// The names file has one identity per line, in hex, followed by its label: 10 digits for an
+/// RFPI, 5 for a PMID. Blank lines and lines starting with `#` are ignored.
+///
+/// ```text
+/// 0123456789 office base
+/// e0a1f      kitchen handset
+/// ```
+#[derive(Debug, Default)]
+pub struct Labels {
+    rfpis: HashMap<u64, String>,
+    pmids: HashMap<u32, String>,
+}
+
+impl Labels {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).with_context(|| format!("{}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("{}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut labels = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (identity, label) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let label = label.trim().to_string();
+            let parsed = u64::from_str_radix(identity, 16).ok();
+            match (identity.len(), parsed) {
+                (10, Some(rfpi)) => labels.rfpis.insert(rfpi, label),
+                (5, Some(pmid)) => labels.pmids.insert(pmid as u32, label),
+                _ => bail!(
+                    "line {}: expected a 10 digit RFPI or 5 digit PMID in hex",
+                    number + 1
+                ),
+            };
+        }
+        Ok(labels)
+    }
+
+    /// The label of the identity in an A-field identities (Nt) tail, if there is one.
+    ///
+    /// A fixed part sends its RFPI as the whole tail, a portable part its PMID in the first 20
+    /// bits. The tail alone does not say which, so it takes the `sender` of the burst; without
+    /// one there is no label.
+    pub fn lookup(&self, packet: &Packet, sender: Option<Sender>) -> Option<&str> {
+        let Packet::A { header, tail, .. } = packet else {
+            return None;
+        };
+        // TA 010 and 011: identities, with or without connectionless data.
+        if !matches!(header >> 5, 2 | 3) {
+            return None;
+        }
+        let tail = tail
+            .iter()
+            .fold(0u64, |acc, byte| acc << 8 | u64::from(*byte));
+        let label = match sender? {
+            Sender::FixedPart => self.rfpis.get(&tail),
+            Sender::PortablePart => self.pmids.get(&((tail >> 20) as u32)),
+        };
+        label.map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dectdump::decoder::{Packet, Sender};
+
+    use super::Labels;
+
+    fn a(header: u8, tail: [u8; 5]) -> Packet {
+        Packet::A {
+            header,
+            tail,
+            crc: 0,
+            b: None,
+            corrected: false,
+        }
+    }
+
+    #[test]
+    fn test_labels() {
+        let labels =
+            Labels::parse("# names\n\n0123456789 office base\ne0a1f\tkitchen handset\n").unwrap();
+        let nt = 3 << 5;
+        let mt = 6 << 5;
+        let fp = Some(Sender::FixedPart);
+        let pp = Some(Sender::PortablePart);
+        let rfpi = [0x01, 0x23, 0x45, 0x67, 0x89];
+        assert_eq!(labels.lookup(&a(nt, rfpi), fp), Some("office base"));
+        assert_eq!(labels.lookup(&a(mt, rfpi), fp), None);
+        let pmid = [0xe0, 0xa1, 0xf0, 0, 0];
+        assert_eq!(labels.lookup(&a(nt, pmid), pp), Some("kitchen handset"));
+        assert_eq!(labels.lookup(&a(nt, [0xe0, 0xa1, 0xe0, 0, 0]), pp), None);
+
+        // An RFPI from a portable part or a PMID from a fixed part is a different identity.
+        assert_eq!(labels.lookup(&a(nt, rfpi), pp), None);
+        assert_eq!(labels.lookup(&a(nt, pmid), fp), None);
+        assert_eq!(labels.lookup(&a(nt, rfpi), None), None);
+
+        assert!(Labels::parse("0123 too short").is_err());
+        assert!(Labels::parse("01234567zz not hex").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use dectdump::decoder::{DecodedPacket, Packet, Sender};
+
+pub use self::{
+    color::ColorChoice,
+    heatmap::{Heatmap, HeatmapFormat, HEATMAP_SCHEMA},
+    hexdump::{Hexdump, OutputFormat},
+    labels::Labels,
+    script::Scripted,
+    syslog::Syslog,
+};
+
+mod color;
+mod heatmap;
+mod hexdump;
+mod labels;
+mod script;
+mod syslog;
+
+/// Destination for decoded packets.
+///
+/// The decoder loop hands every packet to a single sink; use [`Sinks`] to have several outputs
+/// active at once.
+#[async_trait]
+pub trait PacketSink: Send {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()>;
+
+    /// Called once after the last packet, to write out anything still buffered.
+    async fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// When a packet arrived, or now if its source did not say.
+fn received(packet: &DecodedPacket) -> SystemTime {
+    match packet.received {
+        Some(received) => UNIX_EPOCH + received,
+        None => SystemTime::now(),
+    }
+}
+
+/// Renders the inferred position in the TDMA structure as `multiframe.frame.slot`.
+///
+/// Unknown parts are `-`, and a `?` follows a frame or slot number that was guessed.
+fn dect_time(packet: &DecodedPacket) -> String {
+    let multiframe = match packet.frame.and_then(|frame| frame.multiframe) {
+        Some(multiframe) => multiframe.to_string(),
+        None => "-".to_string(),
+    };
+    let frame = match packet.frame {
+        Some(frame) => format!("{}{}", frame.number, guessed(frame.confirmed)),
+        None => "-".to_string(),
+    };
+    let slot = match packet.slot {
+        Some(slot) => format!("{}{}", slot.number, guessed(slot.confirmed)),
+        None => "-".to_string(),
+    };
+    format!("{multiframe}.{frame}.{slot}")
+}
+
+fn guessed(confirmed: bool) -> &'static str {
+    if confirmed {
+        ""
+    } else {
+        "?"
+    }
+}
+
+/// Per channel, who sent the last sync word, and so the A-field after it.
+#[derive(Debug, Default)]
+struct Senders(HashMap<usize, Option<Sender>>);
+
+impl Senders {
+    /// Notes who sent a sync word and returns who sent `packet`, if that is known.
+    fn track(&mut self, packet: &DecodedPacket) -> Option<Sender> {
+        if let Packet::Header { sync, .. } = packet.packet {
+            let sender = packet.syncs.classify(u16::from_be(sync));
+            self.0.insert(packet.channel, sender);
+        }
+        self.0.get(&packet.channel).copied().flatten()
+    }
+}
+
+/// Prints packets to stdout, one line each with arrival time, channel and inferred frame timing,
+/// followed by the label of the identity they carry, if any.
+#[derive(Debug, Default)]
+pub struct Stdout {
+    labels: Arc<Labels>,
+    color: bool,
+    senders: Senders,
+}
+
+impl Stdout {
+    pub fn new(labels: Arc<Labels>) -> Self {
+        Self {
+            labels,
+            ..Self::default()
+        }
+    }
+
+    /// Colors packets by kind, tail type, sender and CRC repair (see [`color::style`]).
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+#[async_trait]
+impl PacketSink for Stdout {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()> {
+        let sender = self.senders.track(packet);
+        let label = match self.labels.lookup(&packet.packet, sender) {
+            Some(label) => format!(" [{label}]"),
+            None => String::new(),
+        };
+        let mut text = format!("{}{label}", packet.packet.display_with(packet.syncs));
+        if self.color {
+            text = color::paint(&text, &color::style(&packet.packet, sender));
+        }
+        println!(
+            "{} {} {}: {text}",
+            humantime::format_rfc3339_micros(received(packet)),
+            packet.channel,
+            dect_time(packet),
+        );
+        Ok(())
+    }
+}
+
+/// Fans every packet out to all contained sinks, in insertion order.
+#[derive(Default)]
+pub struct Sinks {
+    inner: Vec<Box<dyn PacketSink>>,
+}
+
+impl Sinks {
+    pub fn push(&mut self, sink: impl PacketSink + 'static) {
+        self.inner.push(Box::new(sink));
+    }
+}
+
+#[async_trait]
+impl PacketSink for Sinks {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()> {
+        for sink in &mut self.inner {
+            sink.emit(packet).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        for sink in &mut self.inner {
+            sink.finish().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use super::{PacketSink, Sinks};
+    use dectdump::decoder::{DecodedPacket, Packet, SyncWords};
+
+    struct Count(Arc<Mutex<usize>>);
+
+    #[async_trait]
+    impl PacketSink for Count {
+        async fn emit(&mut self, _packet: &DecodedPacket) -> anyhow::Result<()> {
+            *self.0.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sinks_fan_out() {
+        let first = Arc::new(Mutex::new(0));
+        let second = Arc::new(Mutex::new(0));
+
+        let mut sinks = Sinks::default();
+        sinks.push(Count(first.clone()));
+        sinks.push(Count(second.clone()));
+
+        let packet = DecodedPacket {
+            channel: 0,
+            carrier: None,
+            position: 0,
+            frame: None,
+            slot: None,
+            received: None,
+            syncs: SyncWords::DECT,
+            packet: Packet::A {
+                header: 0,
+                tail: [0; 5],
+                crc: 0,
+                b: None,
+                corrected: false,
+            },
+        };
+        sinks.emit(&packet).await.unwrap();
+        sinks.emit(&packet).await.unwrap();
+
+        assert_eq!(*first.lock().unwrap(), 2);
+        assert_eq!(*second.lock().unwrap(), 2);
+    }
+}
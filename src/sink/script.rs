@@ -0,0 +1,305 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use dectdump::decoder::{DecodedPacket, Packet};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+use crate::sink::PacketSink;
+
+const ON_PACKET: &str = "on_packet";
+
+/// Runs a Rhai script hook for every packet before handing it on to the wrapped sink.
+///
+/// The script may define `fn on_packet(packet)`. Returning `false` drops the packet; any other
+/// value passes it through. Top-level statements run once at load time and can set up state for
+/// the hook.
+///
+/// The packet is an object map. Unknown values are `()`.
+///
+/// - `channel`: the channel index; `carrier`: its RF carrier.
+/// - `position`: the burst's bit offset in the input.
+/// - `burst_slot`, `burst_frame`, `multiframe`: the inferred timing. `slot_confirmed` and
+///   `frame_confirmed` say whether it came from a Qt broadcast.
+/// - `received`: the arrival time in seconds since the Unix epoch.
+/// - `kind`: `"header"`, `"a"` or `"gap"`.
+/// - The [`Packet`] fields, with a header's `channel` as `rf_channel`. `sync` is a reserved word in
+///   Rhai, so read it as `packet["sync"]`.
+pub struct Scripted<S> {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    has_hook: bool,
+    inner: S,
+}
+
+impl<S> Scripted<S> {
+    pub fn load(path: impl AsRef<Path>, inner: S) -> Result<Self> {
+        let engine = Engine::new();
+        let ast = engine
+            .compile_file(path.as_ref().into())
+            .map_err(|e| anyhow!("failed to load {}: {e}", path.as_ref().display()))?;
+
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| anyhow!("script failed: {e}"))?;
+
+        let has_hook = ast
+            .iter_functions()
+            .any(|f| f.name == ON_PACKET && f.params.len() == 1);
+
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            has_hook,
+            inner,
+        })
+    }
+
+    fn on_packet(&mut self, packet: &DecodedPacket) -> Result<bool> {
+        if !self.has_hook {
+            return Ok(true);
+        }
+
+        // The top-level statements already ran in `load`; running them again would reset the
+        // state they set up.
+        let options = CallFnOptions::new().eval_ast(false).rewind_scope(false);
+        let result: Dynamic = self
+            .engine
+            .call_fn_with_options(
+                options,
+                &mut self.scope,
+                &self.ast,
+                ON_PACKET,
+                (to_map(packet),),
+            )
+            .map_err(|e| anyhow!("{ON_PACKET} failed: {e}"))?;
+
+        Ok(result.as_bool().unwrap_or(true))
+    }
+}
+
+#[async_trait]
+impl<S: PacketSink> PacketSink for Scripted<S> {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()> {
+        if self.on_packet(packet)? {
+            self.inner.emit(packet).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        self.inner.finish().await
+    }
+}
+
+fn to_map(packet: &DecodedPacket) -> Map {
+    let mut map = Map::new();
+    map.insert("channel".into(), (packet.channel as i64).into());
+    map.insert(
+        "carrier".into(),
+        packet
+            .carrier
+            .map_or(Dynamic::UNIT, |carrier| (carrier as i64).into()),
+    );
+    map.insert("position".into(), (packet.position as i64).into());
+    map.insert(
+        "burst_slot".into(),
+        packet
+            .slot
+            .map_or(Dynamic::UNIT, |slot| (slot.number as i64).into()),
+    );
+    map.insert(
+        "slot_confirmed".into(),
+        packet.slot.is_some_and(|slot| slot.confirmed).into(),
+    );
+    map.insert(
+        "burst_frame".into(),
+        packet
+            .frame
+            .map_or(Dynamic::UNIT, |frame| (frame.number as i64).into()),
+    );
+    map.insert(
+        "frame_confirmed".into(),
+        packet.frame.is_some_and(|frame| frame.confirmed).into(),
+    );
+    map.insert(
+        "multiframe".into(),
+        packet
+            .frame
+            .and_then(|frame| frame.multiframe)
+            .map_or(Dynamic::UNIT, |multiframe| (multiframe as i64).into()),
+    );
+    map.insert(
+        "received".into(),
+        packet
+            .received
+            .map_or(Dynamic::UNIT, |received| received.as_secs_f64().into()),
+    );
+    match &packet.packet {
+        Packet::Header {
+            rxmode,
+            channel,
+            slot,
+            frameno,
+            rssi,
+            preamble,
+            sync,
+        } => {
+            map.insert("kind".into(), "header".into());
+            map.insert("rxmode".into(), (*rxmode as i64).into());
+            // `channel` is the packet's channel index already.
+            map.insert("rf_channel".into(), (*channel as i64).into());
+            map.insert("slot".into(), (*slot as i64).into());
+            map.insert("frameno".into(), (*frameno as i64).into());
+            map.insert("rssi".into(), (*rssi as i64).into());
+            map.insert("preamble".into(), bytes(preamble).into());
+            map.insert("sync".into(), (u16::from_be(*sync) as i64).into());
+        }
+        Packet::A {
+            header,
+            tail,
+            crc,
+            b,
+            corrected,
+        } => {
+            map.insert("kind".into(), "a".into());
+            map.insert("header".into(), (*header as i64).into());
+            map.insert("tail".into(), bytes(tail).into());
+            map.insert("crc".into(), (*crc as i64).into());
+            map.insert("corrected".into(), (*corrected).into());
+            let b = match b {
+                Some(b) => b
+                    .iter()
+                    .map(|bit| Dynamic::from_bool(*bit))
+                    .collect::<Array>(),
+                None => Array::new(),
+            };
+            map.insert("b".into(), b.into());
+        }
+        Packet::Gap { bits, dropped } => {
+            map.insert("kind".into(), "gap".into());
+            map.insert("bits".into(), (*bits as i64).into());
+            map.insert("dropped".into(), (*dropped).into());
+        }
+    }
+    map
+}
+
+fn bytes(bytes: &[u8]) -> Array {
+    bytes.iter().map(|byte| (*byte as i64).into()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::Scripted;
+    use dectdump::decoder::{DecodedPacket, Packet, SyncWords};
+
+    #[test]
+    fn test_hook_filters_packets() {
+        let mut file = std::env::temp_dir();
+        file.push(format!("dectdump-hook-{}.rhai", std::process::id()));
+        std::fs::File::create(&file)
+            .unwrap()
+            .write_all(b"fn on_packet(packet) { packet.kind == \"a\" && packet.header == 1 }")
+            .unwrap();
+
+        let mut scripted = Scripted::load(&file, ()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let packet = |header| DecodedPacket {
+            channel: 0,
+            carrier: None,
+            position: 0,
+            frame: None,
+            slot: None,
+            received: None,
+            syncs: SyncWords::DECT,
+            packet: Packet::A {
+                header,
+                tail: [0; 5],
+                crc: 0,
+                b: None,
+                corrected: false,
+            },
+        };
+        assert!(scripted.on_packet(&packet(1)).unwrap());
+        assert!(!scripted.on_packet(&packet(2)).unwrap());
+    }
+
+    #[test]
+    fn test_header_channel() {
+        let mut file = std::env::temp_dir();
+        file.push(format!("dectdump-header-{}.rhai", std::process::id()));
+        std::fs::File::create(&file)
+            .unwrap()
+            .write_all(
+                b"fn on_packet(packet) { \
+                  packet.channel == 2 && packet.rf_channel == 0 && packet[\"sync\"] == 0xe98a }",
+            )
+            .unwrap();
+
+        let mut scripted = Scripted::load(&file, ()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let packet = |channel| DecodedPacket {
+            channel,
+            carrier: None,
+            position: 0,
+            frame: None,
+            slot: None,
+            received: None,
+            syncs: SyncWords::DECT,
+            packet: Packet::Header {
+                rxmode: 0,
+                channel: 0,
+                slot: 0,
+                frameno: 0,
+                rssi: 0,
+                preamble: [0xaa; 3],
+                sync: 0xe98a_u16.to_be(),
+            },
+        };
+        assert!(scripted.on_packet(&packet(2)).unwrap());
+        assert!(!scripted.on_packet(&packet(0)).unwrap());
+    }
+
+    #[test]
+    fn test_top_level_state() {
+        let mut file = std::env::temp_dir();
+        file.push(format!("dectdump-state-{}.rhai", std::process::id()));
+        std::fs::File::create(&file)
+            .unwrap()
+            .write_all(
+                b"if is_def_var(\"seen\") { throw \"top level ran again\"; } \
+                  let seen = 0; \
+                  fn on_packet(packet) { seen += 1; seen <= 2 }",
+            )
+            .unwrap();
+
+        let mut scripted = Scripted::load(&file, ()).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let packet = DecodedPacket {
+            channel: 0,
+            carrier: None,
+            position: 0,
+            frame: None,
+            slot: None,
+            received: None,
+            syncs: SyncWords::DECT,
+            packet: Packet::Gap {
+                bits: 1,
+                dropped: false,
+            },
+        };
+        let passed: Vec<_> = (0..4)
+            .map(|_| scripted.on_packet(&packet).unwrap())
+            .collect();
+        assert_eq!(passed, [true, true, false, false]);
+    }
+}
@@ -0,0 +1,199 @@
+use std::{fmt::Write, process, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use dectdump::decoder::{DecodedPacket, Packet};
+use tokio::net::{lookup_host, UdpSocket};
+
+use crate::sink::{dect_time, received, Labels, PacketSink, Senders};
+
+const APP_NAME: &str = "dectdump";
+
+/// `user` facility, `informational` severity.
+const PRI: u8 = 8 + 6;
+
+/// Sends decoded packets to a syslog receiver as RFC 5424 messages over UDP (RFC 5426).
+#[derive(Debug)]
+pub struct Syslog {
+    socket: UdpSocket,
+    hostname: String,
+    procid: u32,
+    labels: Arc<Labels>,
+    senders: Senders,
+    /// Messages that could not be sent.
+    failed: u64,
+}
+
+impl Syslog {
+    pub async fn connect(addr: &str) -> Result<Self> {
+        let target = match lookup_host(addr).await?.next() {
+            Some(target) => target,
+            None => anyhow::bail!("could not resolve syslog address {addr}"),
+        };
+
+        let bind = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(target).await?;
+
+        let hostname = gethostname::gethostname()
+            .into_string()
+            .unwrap_or_else(|_| "-".to_string());
+
+        Ok(Self {
+            socket,
+            hostname,
+            procid: process::id(),
+            labels: Arc::default(),
+            senders: Senders::default(),
+            failed: 0,
+        })
+    }
+
+    /// Adds a `label=` field to packets carrying an identity with a label.
+    pub fn with_labels(mut self, labels: Arc<Labels>) -> Self {
+        self.labels = labels;
+        self
+    }
+}
+
+#[async_trait]
+impl PacketSink for Syslog {
+    async fn emit(&mut self, packet: &DecodedPacket) -> Result<()> {
+        let sender = self.senders.track(packet);
+        let (msgid, msg) = describe(&packet.packet);
+        let mut line = format!(
+            "<{PRI}>1 {} {} {APP_NAME} {} {msgid} - channel={}",
+            humantime::format_rfc3339_micros(received(packet)),
+            self.hostname,
+            self.procid,
+            packet.channel,
+        );
+        if let Some(carrier) = packet.carrier {
+            let _ = write!(line, " carrier={carrier}");
+        }
+        let _ = write!(
+            line,
+            " position={} time={}",
+            packet.position,
+            dect_time(packet)
+        );
+        if let Some(label) = self.labels.lookup(&packet.packet, sender) {
+            let _ = write!(line, " label={label:?}");
+        }
+        let _ = write!(line, " {msg}");
+
+        // A receiver that is down must not stop decoding. While it is, only every other send
+        // reports the refusal, so count the failures and sum them up at the end.
+        if let Err(e) = self.socket.send(line.as_bytes()).await {
+            if self.failed == 0 {
+                eprintln!("syslog: cannot send, dropping messages while this lasts: {e}");
+            }
+            self.failed += 1;
+        }
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        if self.failed > 0 {
+            eprintln!("syslog: {} messages could not be sent", self.failed);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the MSGID and a compact `key=value` rendering of a packet.
+fn describe(packet: &Packet) -> (&'static str, String) {
+    match packet {
+        Packet::Header {
+            rxmode,
+            channel,
+            slot,
+            frameno,
+            rssi,
+            preamble,
+            sync,
+        } => (
+            "HEADER",
+            format!(
+                "rxmode={rxmode} channel={channel} slot={slot} frameno={frameno} rssi={rssi} \
+                 preamble={:02x}{:02x}{:02x} sync={:04x}",
+                preamble[0],
+                preamble[1],
+                preamble[2],
+                u16::from_be(*sync)
+            ),
+        ),
+        Packet::A {
+            header,
+            tail,
+            crc,
+            b,
+            corrected,
+        } => {
+            let mut msg = format!("header={header:02x} tail=");
+            for byte in tail {
+                let _ = write!(msg, "{byte:02x}");
+            }
+            let _ = write!(msg, " crc={crc:04x}");
+            if *corrected {
+                msg.push_str(" corrected");
+            }
+            if let Some(b) = b {
+                msg.push_str(" b=");
+                msg.extend(b.iter().map(|bit| if *bit { '1' } else { '0' }));
+            }
+            ("A", msg)
+        }
+        Packet::Gap { bits, dropped } => (
+            "GAP",
+            format!(
+                "bits={bits} frames={} dropped={dropped}",
+                bits / dectdump::decoder::FRAME_BITS
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use dectdump::decoder::{DecodedPacket, Packet, SyncWords};
+    use tokio::{net::UdpSocket, time};
+
+    use super::Syslog;
+    use crate::sink::PacketSink;
+
+    #[tokio::test]
+    async fn test_receiver_down() {
+        // A port nobody listens on any more, so sends are refused.
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = receiver.local_addr().unwrap();
+        drop(receiver);
+
+        let mut syslog = Syslog::connect(&addr.to_string()).await.unwrap();
+        let packet = DecodedPacket {
+            channel: 0,
+            carrier: None,
+            position: 0,
+            frame: None,
+            slot: None,
+            received: None,
+            syncs: SyncWords::DECT,
+            packet: Packet::Gap {
+                bits: 1,
+                dropped: false,
+            },
+        };
+        for _ in 0..3 {
+            syslog.emit(&packet).await.unwrap();
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(syslog.failed > 0);
+        syslog.finish().await.unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+//! Input sources for [`DecoderStream`](crate::stream::DecoderStream).
+
+use std::{
+    io::{self, SeekFrom},
+    net::{Ipv4Addr, SocketAddrV4},
+    path::Path,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncSeekExt, ReadBuf},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+};
+
+/// A bit stream sent by a demodulator over UDP; every datagram is one chunk of input.
+#[derive(Debug)]
+pub struct UdpSource(UdpSocket);
+
+impl UdpSource {
+    /// Listens on `port` on all interfaces.
+    pub async fn bind(port: u16) -> io::Result<Self> {
+        let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port);
+        Ok(Self(UdpSocket::bind(addr).await?))
+    }
+}
+
+impl Stream for UdpSource {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut buf = [0u8; 2048];
+        let mut buf = ReadBuf::new(&mut buf);
+        ready!(self.0.poll_recv(cx, &mut buf))?;
+        Poll::Ready(Some(Ok(buf.filled().to_vec())))
+    }
+}
+
+/// A capture file holding the raw bit stream, read in chunks until its end.
+#[derive(Debug)]
+pub struct FileSource(File);
+
+impl FileSource {
+    /// Opens the capture at `path`.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self(File::open(path).await?))
+    }
+
+    /// Continues reading at `offset` bytes from the start of the file.
+    pub async fn seek(&mut self, offset: u64) -> io::Result<()> {
+        self.0.seek(SeekFrom::Start(offset)).await?;
+        Ok(())
+    }
+}
+
+impl Stream for FileSource {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        poll_chunk(Pin::new(&mut self.0), cx)
+    }
+}
+
+/// A bit stream read from a TCP connection, e.g. relayed by a probe near the antenna. Ends when
+/// the other side closes the connection.
+#[derive(Debug)]
+pub struct TcpSource(TcpStream);
+
+impl TcpSource {
+    /// Connects to `addr`, e.g. `probe.local:2323`.
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(Self(stream))
+    }
+}
+
+impl Stream for TcpSource {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        poll_chunk(Pin::new(&mut self.0), cx)
+    }
+}
+
+/// Reads whatever `reader` has ready, up to 64 KiB, as one chunk; `None` at its end.
+fn poll_chunk(
+    reader: Pin<&mut impl AsyncRead>,
+    cx: &mut Context<'_>,
+) -> Poll<Option<io::Result<Vec<u8>>>> {
+    let mut buf = [0u8; 64 * 1024];
+    let mut buf = ReadBuf::new(&mut buf);
+    ready!(reader.poll_read(cx, &mut buf))?;
+    if buf.filled().is_empty() {
+        return Poll::Ready(None);
+    }
+    Poll::Ready(Some(Ok(buf.filled().to_vec())))
+}
@@ -0,0 +1,176 @@
+//! Driving a decoder from an asynchronous source of input.
+
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{
+    clock::{Clock, WallClock},
+    decoder::{DecodedPacket, Decoder},
+    error::Result,
+};
+
+/// Anything that yields chunks of the raw bit stream, such as a socket or a file reader.
+pub trait DataSource: Stream<Item = io::Result<Vec<u8>>> + Unpin {}
+
+impl<T: Stream<Item = io::Result<Vec<u8>>> + Unpin> DataSource for T {}
+
+/// Drives a [`Decoder`] from a [`DataSource`], feeding it input whenever it runs dry.
+///
+/// Ends when the source does. A source error is yielded as an item and decoding carries on.
+#[derive(Debug)]
+pub struct DecoderStream<S> {
+    source: S,
+    decoder: Decoder,
+    channel: usize,
+    carrier: Option<u8>,
+    clock: Box<dyn Clock>,
+    /// Bits the source threw away, not yet reported.
+    dropped: Option<Arc<AtomicU64>>,
+    done: bool,
+}
+
+impl<S: DataSource> DecoderStream<S> {
+    /// Decodes the bits `source` yields with `decoder`.
+    pub fn new(source: S, decoder: Decoder) -> Self {
+        Self {
+            source,
+            decoder,
+            channel: 0,
+            carrier: None,
+            clock: Box::new(WallClock::default()),
+            dropped: None,
+            done: false,
+        }
+    }
+
+    /// Sets the channel index packets are tagged with (0 by default).
+    pub fn with_channel(mut self, channel: usize) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Sets the RF carrier packets are tagged with.
+    pub fn with_carrier(mut self, carrier: u8) -> Self {
+        self.carrier = Some(carrier);
+        self
+    }
+
+    /// Sets where packet receive times come from ([`WallClock`] by default).
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Shares a count of bits the source had to throw away, e.g. datagrams that arrived while the
+    /// decoder was busy. The source adds to it, and the bits are reported as a dropped gap before
+    /// the next chunk is decoded.
+    pub fn with_dropped(mut self, dropped: Arc<AtomicU64>) -> Self {
+        self.dropped = Some(dropped);
+        self
+    }
+
+    /// The decoder, e.g. to check its [`Lock`](crate::decoder::Lock) state.
+    pub fn decoder(&self) -> &Decoder {
+        &self.decoder
+    }
+}
+
+impl<S: DataSource> Stream for DecoderStream<S> {
+    type Item = Result<DecodedPacket>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(packet) = this.decoder.next_packet()? {
+                let position = this.decoder.burst_position();
+                return Poll::Ready(Some(Ok(DecodedPacket {
+                    channel: this.channel,
+                    carrier: this.carrier,
+                    position,
+                    frame: this.decoder.burst_frame(),
+                    slot: this.decoder.burst_slot(),
+                    received: this.clock.time(position),
+                    syncs: this.decoder.sync_words(),
+                    packet,
+                })));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            match ready!(Pin::new(&mut this.source).poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    this.clock.chunk_received();
+                    let dropped = this
+                        .dropped
+                        .as_ref()
+                        .map_or(0, |d| d.swap(0, Ordering::Relaxed));
+                    if dropped > 0 {
+                        this.decoder.discard(dropped);
+                    }
+                    this.decoder.extend(chunk);
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                None => this.done = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use tokio_stream::StreamExt;
+
+    use super::DecoderStream;
+    use crate::{
+        decoder::{BitIterator, Decoder, Packet},
+        synth::single_burst,
+    };
+
+    #[tokio::test]
+    async fn test_decoder_stream() {
+        // Hand the capture over in small, unaligned pieces with an error in between.
+        let (data, planted) = single_burst();
+        let mut chunks: Vec<_> = data.chunks(37).map(|c| Ok(c.to_vec())).collect();
+        chunks.insert(1, Err(io::Error::other("hiccup")));
+
+        let decoder = Decoder::new(BitIterator::new([]));
+        let stream = DecoderStream::new(tokio_stream::iter(chunks), decoder)
+            .with_channel(2)
+            .with_carrier(5);
+        let items: Vec<_> = stream.collect().await;
+
+        assert!(items[0].is_err());
+        let packets: Vec<_> = items[1..].iter().map(|p| p.as_ref().unwrap()).collect();
+        assert!(packets
+            .iter()
+            .all(|p| p.channel == 2 && p.carrier == Some(5)));
+        assert!(packets
+            .iter()
+            .all(|p| p.position == planted.position as u64 && p.received.is_some()));
+        assert!(matches!(
+            packets[..],
+            [
+                super::DecodedPacket {
+                    packet: Packet::Header { .. },
+                    ..
+                },
+                super::DecodedPacket {
+                    packet: Packet::A { b: Some(_), .. },
+                    ..
+                }
+            ]
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+//! Synthetic captures: random but reproducible traffic on the slot grid, with impairments.
+//!
+//! Used by the tests and by `dectdump generate`. The output is in the same format a demodulator
+//! sends, one bit per bit, MSB first.
+
+use alloc::vec::Vec;
+
+use bitvec::{order::Msb0, vec::BitVec};
+
+use crate::{
+    decoder::{B_FIELD_BITS, SLOT_BITS},
+    encode::{AField, Burst},
+};
+
+/// Describes a synthetic capture; [`generate`](Self::generate) builds it.
+#[derive(Debug, Clone)]
+pub struct Synth {
+    seed: u64,
+    slots: usize,
+    occupancy: f64,
+    fp_ratio: f64,
+    b_field_ratio: f64,
+    noise: bool,
+    bit_error_rate: f64,
+    crc_error_rate: f64,
+}
+
+/// A burst placed in a synthetic capture.
+#[derive(Debug, Clone)]
+pub struct Planted {
+    /// Bit offset of the first S-field bit.
+    pub position: usize,
+    /// The burst as sent, before bit errors.
+    pub burst: Burst,
+    /// The R-CRC was deliberately damaged, so the burst should not decode.
+    pub crc_corrupted: bool,
+}
+
+impl Planted {
+    /// Bit offset of the 24-bit sync word the decoder searches for.
+    pub fn sync_position(&self) -> usize {
+        self.position + 8
+    }
+
+    /// Bit offset of the A-field.
+    pub fn a_field_position(&self) -> usize {
+        self.position + 32
+    }
+}
+
+/// A generated capture and what went into it.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    /// The bit stream.
+    pub bits: BitVec<u8, Msb0>,
+    /// Every burst, in order.
+    pub bursts: Vec<Planted>,
+    /// Number of bits flipped by the bit error rate.
+    pub bit_errors: usize,
+}
+
+impl Capture {
+    /// The bit stream as bytes, zero-padded to a whole byte.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bits.into_vec()
+    }
+}
+
+impl Synth {
+    /// One frame (24 slots), half of them busy with an even FP/PP mix, half the bursts with a
+    /// B-field, noise between the bursts and no errors.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            slots: 24,
+            occupancy: 0.5,
+            fp_ratio: 0.5,
+            b_field_ratio: 0.5,
+            noise: true,
+            bit_error_rate: 0.0,
+            crc_error_rate: 0.0,
+        }
+    }
+
+    /// Sets the number of slots. An idle slot is added before and after them.
+    pub fn with_slots(mut self, slots: usize) -> Self {
+        self.slots = slots;
+        self
+    }
+
+    /// Sets the fraction of slots that carry a burst.
+    pub fn with_occupancy(mut self, occupancy: f64) -> Self {
+        self.occupancy = occupancy;
+        self
+    }
+
+    /// Sets the fraction of bursts sent by a fixed part; the rest come from portable parts.
+    pub fn with_fp_ratio(mut self, fp_ratio: f64) -> Self {
+        self.fp_ratio = fp_ratio;
+        self
+    }
+
+    /// Sets the fraction of bursts that carry a random B-field.
+    pub fn with_b_field_ratio(mut self, b_field_ratio: f64) -> Self {
+        self.b_field_ratio = b_field_ratio;
+        self
+    }
+
+    /// Fills the gaps between bursts with random bits instead of zeros.
+    pub fn with_noise(mut self, noise: bool) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    /// Sets the probability of flipping each bit of the finished capture.
+    pub fn with_bit_error_rate(mut self, rate: f64) -> Self {
+        self.bit_error_rate = rate;
+        self
+    }
+
+    /// Sets the fraction of bursts whose R-CRC is damaged beyond single bit correction.
+    pub fn with_crc_error_rate(mut self, rate: f64) -> Self {
+        self.crc_error_rate = rate;
+        self
+    }
+
+    /// Builds the capture. The same settings always give the same capture.
+    pub fn generate(&self) -> Capture {
+        let mut rng = Rng::new(self.seed);
+        let mut bits = BitVec::<u8, Msb0>::new();
+        let mut bursts = Vec::new();
+
+        // The base station all fixed part bursts come from.
+        let rfpi = rng.next().to_be_bytes();
+        let rfpi = [rfpi[0], rfpi[1], rfpi[2], rfpi[3], rfpi[4]];
+
+        // Leading idle slot, plus a few bits so the grid is not byte aligned.
+        let lead = SLOT_BITS as usize + (rng.next() % 8) as usize;
+        self.idle(&mut rng, &mut bits, lead);
+
+        for _ in 0..self.slots {
+            let start = bits.len();
+            if rng.chance(self.occupancy) {
+                let burst = self.burst(&mut rng, rfpi);
+                burst.write(&mut bits);
+
+                let crc_corrupted = rng.chance(self.crc_error_rate);
+                if crc_corrupted {
+                    // At least two bits, so correction can't repair it.
+                    let mask = (rng.next() as u16) | 0x0101;
+                    let crc = &mut bits[start + 32 + 48..start + 32 + 64];
+                    for (i, mut bit) in crc.iter_mut().enumerate() {
+                        *bit ^= mask >> (15 - i) & 1 != 0;
+                    }
+                }
+
+                bursts.push(Planted {
+                    position: start,
+                    burst,
+                    crc_corrupted,
+                });
+            }
+            let used = bits.len() - start;
+            self.idle(&mut rng, &mut bits, SLOT_BITS as usize - used);
+        }
+        self.idle(&mut rng, &mut bits, SLOT_BITS as usize);
+
+        let mut bit_errors = 0;
+        if self.bit_error_rate > 0.0 {
+            for mut bit in bits.iter_mut() {
+                if rng.chance(self.bit_error_rate) {
+                    *bit = !*bit;
+                    bit_errors += 1;
+                }
+            }
+        }
+
+        Capture {
+            bits,
+            bursts,
+            bit_errors,
+        }
+    }
+
+    fn burst(&self, rng: &mut Rng, rfpi: [u8; 5]) -> Burst {
+        // The A-field header says "no B-field" until one is attached.
+        let burst = if rng.chance(self.fp_ratio) {
+            Burst::fp(AField::nt(rfpi))
+        } else {
+            let params = rng.next();
+            Burst::pp(AField::mt((params >> 32) as u8, params as u32))
+        };
+        if !rng.chance(self.b_field_ratio) {
+            return burst;
+        }
+
+        let mut b_field = BitVec::with_capacity(B_FIELD_BITS);
+        while b_field.len() < B_FIELD_BITS {
+            b_field.extend_from_raw_slice(&rng.next().to_be_bytes());
+        }
+        burst.with_b_field(b_field)
+    }
+
+    fn idle(&self, rng: &mut Rng, bits: &mut BitVec<u8, Msb0>, len: usize) {
+        let end = bits.len() + len;
+        if !self.noise {
+            bits.resize(end, false);
+            return;
+        }
+        while bits.len() < end {
+            bits.extend_from_raw_slice(&rng.next().to_be_bytes());
+        }
+        bits.truncate(end);
+    }
+}
+
+/// Small PCG-style generator; good enough for test traffic and reproducible everywhere.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        let mut rng = Self(seed ^ 0x853c_49e6_748f_ea9b);
+        rng.next();
+        rng
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let x = self.0;
+        (x ^ (x >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd) ^ (x >> 29)
+    }
+
+    /// True with probability `p`.
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next() >> 11) as f64) < p * (1u64 << 53) as f64
+    }
+}
+
+/// One FP burst with a B-field in noise, for tests: the capture and the planted burst.
+#[cfg(test)]
+pub(crate) fn single_burst() -> (Vec<u8>, Planted) {
+    let capture = Synth::new(1)
+        .with_slots(1)
+        .with_occupancy(1.0)
+        .with_fp_ratio(1.0)
+        .with_b_field_ratio(1.0)
+        .generate();
+    let planted = capture.bursts[0].clone();
+    (capture.into_bytes(), planted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Synth;
+    use crate::{
+        decoder::{decode_all, Packet, SLOT_BITS},
+        error::Result,
+    };
+
+    fn count_a_fields(bytes: &[u8]) -> Result<usize> {
+        let packets = decode_all(bytes)?;
+        Ok(packets
+            .iter()
+            .filter(|p| matches!(p, Packet::A { .. }))
+            .count())
+    }
+
+    #[test]
+    fn test_layout() {
+        let synth = Synth::new(3).with_slots(48).with_noise(false);
+        let capture = synth.generate();
+        // A lead-in slot plus up to 7 bits, the slots, and a trailing idle slot.
+        let lead = capture.bits.len() - 49 * SLOT_BITS as usize;
+        assert!((480..488).contains(&lead));
+        assert_eq!(capture.bursts[0].position % SLOT_BITS as usize, lead % 480);
+        assert!(!capture.bursts.is_empty() && capture.bursts.len() < 48);
+
+        let first = capture.bursts[0].position;
+        for planted in &capture.bursts {
+            assert_eq!((planted.position - first) % SLOT_BITS as usize, 0);
+        }
+
+        // Reproducible.
+        assert_eq!(synth.generate().bits, capture.bits);
+        assert_ne!(Synth::new(4).generate().bits, capture.bits);
+    }
+
+    #[test]
+    fn test_decodes() {
+        let capture = Synth::new(5).with_slots(240).generate();
+        let bursts = capture.bursts.len();
+        assert_eq!(count_a_fields(&capture.into_bytes()).unwrap(), bursts);
+    }
+
+    #[test]
+    fn test_impairments() {
+        let capture = Synth::new(6)
+            .with_slots(240)
+            .with_occupancy(1.0)
+            .with_crc_error_rate(0.25)
+            .generate();
+        let good = capture.bursts.iter().filter(|b| !b.crc_corrupted).count();
+        assert!(good > 120 && good < 240);
+        assert_eq!(count_a_fields(&capture.into_bytes()).unwrap(), good);
+
+        let capture = Synth::new(6).with_bit_error_rate(0.01).generate();
+        let expected = capture.bits.len() as f64 * 0.01;
+        assert!((capture.bit_errors as f64 - expected).abs() < expected / 2.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! JavaScript API, for analyzing captures in the browser.
+//!
+//! Build with
+//!
+//! ```sh
+//! cargo rustc --release --lib --crate-type cdylib --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/dectdump.wasm
+//! ```
+//!
+//! then:
+//!
+//! ```js
+//! import init, { decodeAll, Decoder } from "./pkg/dectdump.js";
+//! await init();
+//! for (const packet of decodeAll(new Uint8Array(await file.arrayBuffer()))) {
+//!     console.log(packet.kind, packet.tail);
+//! }
+//! ```
+//!
+//! or, to read the file in pieces and repair single bit errors:
+//!
+//! ```js
+//! const decoder = new Decoder(true);
+//! for await (const chunk of file.stream()) {
+//!     for (const packet of decoder.push(chunk)) {
+//!         console.log(packet.kind, packet.tail);
+//!     }
+//! }
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use wasm_bindgen::prelude::*;
+
+use crate::decoder::{self, BitIterator, Packet as RawPacket};
+
+/// A decoded packet as seen from JavaScript.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Packet {
+    kind: &'static str,
+    sync: u16,
+    header: u8,
+    tail: Vec<u8>,
+    crc: u16,
+    b: Option<Vec<u8>>,
+    corrected: bool,
+    gap_bits: u64,
+    dropped: bool,
+}
+
+#[wasm_bindgen]
+impl Packet {
+    /// `"header"` for a sync, `"a"` for an A-field, `"gap"` for a stretch of input without
+    /// decoded bursts.
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.into()
+    }
+
+    /// Low 16 bits of the sync word, e.g. `0xe98a` from a fixed part (headers only).
+    #[wasm_bindgen(getter)]
+    pub fn sync(&self) -> u16 {
+        self.sync
+    }
+
+    /// A-field header octet.
+    #[wasm_bindgen(getter)]
+    pub fn header(&self) -> u8 {
+        self.header
+    }
+
+    /// The 40-bit A-field tail.
+    #[wasm_bindgen(getter)]
+    pub fn tail(&self) -> Vec<u8> {
+        self.tail.clone()
+    }
+
+    /// The received R-CRC.
+    #[wasm_bindgen(getter)]
+    pub fn crc(&self) -> u16 {
+        self.crc
+    }
+
+    /// The B-field, padded to whole bytes, or `undefined`.
+    #[wasm_bindgen(getter)]
+    pub fn b(&self) -> Option<Vec<u8>> {
+        self.b.clone()
+    }
+
+    /// Whether a bit error in the A-field was corrected.
+    #[wasm_bindgen(getter)]
+    pub fn corrected(&self) -> bool {
+        self.corrected
+    }
+
+    /// Length of a gap in bits (gaps only).
+    #[wasm_bindgen(getter, js_name = gapBits)]
+    pub fn gap_bits(&self) -> f64 {
+        self.gap_bits as f64
+    }
+
+    /// Whether the gap's input was discarded unread (gaps only).
+    #[wasm_bindgen(getter)]
+    pub fn dropped(&self) -> bool {
+        self.dropped
+    }
+}
+
+impl From<RawPacket> for Packet {
+    fn from(packet: RawPacket) -> Self {
+        match packet {
+            RawPacket::Header { sync, .. } => Self {
+                kind: "header",
+                sync: u16::from_be(sync),
+                header: 0,
+                tail: Vec::new(),
+                crc: 0,
+                b: None,
+                corrected: false,
+                gap_bits: 0,
+                dropped: false,
+            },
+            RawPacket::A {
+                header,
+                tail,
+                crc,
+                b,
+                corrected,
+            } => Self {
+                kind: "a",
+                sync: 0,
+                header,
+                tail: tail.to_vec(),
+                crc,
+                b: b.map(|b| decoder::pack_bits(&b)),
+                corrected,
+                gap_bits: 0,
+                dropped: false,
+            },
+            RawPacket::Gap { bits, dropped } => Self {
+                kind: "gap",
+                sync: 0,
+                header: 0,
+                tail: Vec::new(),
+                crc: 0,
+                b: None,
+                corrected: false,
+                gap_bits: bits,
+                dropped,
+            },
+        }
+    }
+}
+
+/// Incremental decoder for input read in pieces.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Decoder(decoder::Decoder);
+
+#[wasm_bindgen]
+impl Decoder {
+    /// Creates a decoder, optionally repairing single bit A-field errors.
+    #[wasm_bindgen(constructor)]
+    pub fn new(correct_errors: bool) -> Self {
+        let decoder =
+            decoder::Decoder::new(BitIterator::new([])).with_error_correction(correct_errors);
+        Self(decoder)
+    }
+
+    /// Appends `data` and returns the packets completed by it.
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<Packet>, JsError> {
+        let packets = self
+            .0
+            .push(data)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(packets.into_iter().map(Packet::from).collect())
+    }
+}
+
+/// Decodes a complete capture.
+#[wasm_bindgen(js_name = decodeAll)]
+pub fn decode_all(capture: &[u8]) -> Result<Vec<Packet>, JsError> {
+    let packets = decoder::decode_all(capture).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(packets.into_iter().map(Packet::from).collect())
+}
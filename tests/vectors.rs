@@ -0,0 +1,92 @@
+//! Golden test vectors: reference captures under `tests/vectors/`, each decoded and compared
+//! against the packets recorded next to it.
+//!
+//! A vector is a raw bit stream `<name>.bin` (one bit per bit, MSB first, as a demodulator sends
+//! it) and `<name>.json` with the expected packets. Run with `UPDATE_VECTORS=1` to rewrite the
+//! JSON files from the current decoder output, then review the diff.
+
+use std::{env, fs, path::Path};
+
+use dectdump::decoder::{decode_all, pack_bits, Packet};
+use serde_json::{json, Value};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn to_json(packet: &Packet) -> Value {
+    match packet {
+        Packet::Header {
+            rxmode,
+            channel,
+            slot,
+            frameno,
+            rssi,
+            preamble,
+            sync,
+        } => json!({
+            "type": "header",
+            "rxmode": rxmode,
+            "channel": channel,
+            "slot": slot,
+            "frameno": frameno,
+            "rssi": rssi,
+            "preamble": hex(preamble),
+            "sync": format!("{:04x}", u16::from_be(*sync)),
+        }),
+        Packet::A {
+            header,
+            tail,
+            crc,
+            b,
+            corrected,
+        } => json!({
+            "type": "a",
+            "header": format!("{header:02x}"),
+            "tail": hex(tail),
+            "crc": format!("{crc:04x}"),
+            "b": b.as_ref().map(|b| hex(&pack_bits(b))),
+            "corrected": corrected,
+        }),
+        Packet::Gap { bits, dropped } => json!({
+            "type": "gap",
+            "bits": bits,
+            "dropped": dropped,
+        }),
+    }
+}
+
+#[test]
+fn test_vectors() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vectors");
+    let update = env::var_os("UPDATE_VECTORS").is_some();
+
+    let mut vectors: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bin"))
+        .collect();
+    vectors.sort();
+    assert!(!vectors.is_empty(), "no vectors in {}", dir.display());
+
+    for path in vectors {
+        let capture = fs::read(&path).unwrap();
+        let packets = decode_all(&capture).unwrap();
+        let actual = Value::Array(packets.iter().map(to_json).collect());
+
+        let expected_path = path.with_extension("json");
+        if update {
+            let mut text = serde_json::to_string_pretty(&actual).unwrap();
+            text.push('\n');
+            fs::write(&expected_path, text).unwrap();
+            continue;
+        }
+
+        let expected: Value = serde_json::from_str(
+            &fs::read_to_string(&expected_path)
+                .unwrap_or_else(|e| panic!("{}: {e}", expected_path.display())),
+        )
+        .unwrap();
+        assert_eq!(actual, expected, "{}", path.display());
+    }
+}